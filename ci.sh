@@ -34,5 +34,9 @@ echo ""
 echo "==> Running tests (cargo test --all --locked)..."
 cargo test --all --locked
 
+echo ""
+echo "==> Running svgz feature tests..."
+cargo test --locked --features svgz --lib svgz
+
 echo ""
 echo "==> All CI checks passed!"
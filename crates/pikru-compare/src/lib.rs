@@ -50,7 +50,12 @@ pub enum CompareResult {
         rust_output: String,
     },
     /// SVG outputs differ (includes SSIM score and structural diff)
-    SvgMismatch { ssim: f64, details: String },
+    SvgMismatch {
+        ssim: f64,
+        details: String,
+        /// One-line summary of the first differing element, see [`first_difference`]
+        first_difference: Option<String>,
+    },
     /// Failed to parse one of the SVGs
     ParseError { details: String },
     /// Failed to render one of the SVGs
@@ -162,19 +167,234 @@ fn normalize_html_entities(svg: &str) -> String {
     result
 }
 
-/// Render SVG to a pixel buffer using resvg
-fn render_svg_to_pixels(svg_content: &str) -> Result<image::RgbaImage, String> {
+/// Rewrite `rgba()` fill and stroke colors as `rgb()` plus `fill-opacity`/`stroke-opacity`,
+/// in both style declarations and presentation attributes, so the two ways of writing
+/// a translucent color compare equal.
+fn normalize_opacity(svg: &str) -> String {
+    let mut result = String::with_capacity(svg.len());
+    let mut rest = svg;
+    while let Some(start) = rest.find("rgba(") {
+        let Some(len) = rest[start..].find(')') else {
+            break;
+        };
+        let end = start + len + 1;
+        let before = &rest[..start];
+        let args: Vec<&str> = rest[start + 5..end - 1].split(',').map(str::trim).collect();
+        let context = ["fill", "stroke"].into_iter().find_map(|prop| {
+            if before.ends_with(&format!("{}:", prop)) {
+                Some((prop, true))
+            } else if before.ends_with(&format!("{}=\"", prop)) {
+                Some((prop, false))
+            } else {
+                None
+            }
+        });
+        result.push_str(before);
+        match (context, args.as_slice()) {
+            (Some((prop, in_style)), [r, g, b, a]) => {
+                result.push_str(&format!("rgb({},{},{})", r, g, b));
+                if in_style {
+                    result.push_str(&format!(";{}-opacity:{}", prop, a));
+                } else {
+                    // The attribute's closing quote follows in `rest`
+                    result.push_str(&format!("\" {}-opacity=\"{}", prop, a));
+                }
+            }
+            _ => result.push_str(&rest[start..end]),
+        }
+        rest = &rest[end..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Normalize print/HTML output for comparison: `<br>` variants count as line breaks,
+/// and runs of whitespace collapse to a single space.
+fn normalize_non_svg_text(output: &str) -> String {
+    output
+        .replace("<br />", "\n")
+        .replace("<br/>", "\n")
+        .replace("<br>", "\n")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// An element of serialized SVG, as walked by [`first_difference`]
+struct Element<'a> {
+    name: &'a str,
+    /// Attributes in source order; text content is included as `#text`
+    attrs: Vec<(&'a str, String)>,
+}
+
+/// Split SVG markup into its elements in document order. Only start tags are
+/// kept; the text up to the next tag counts towards the element before it.
+fn svg_elements(svg: &str) -> Vec<Element<'_>> {
+    let mut elements: Vec<Element> = Vec::new();
+    let mut rest = svg;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let end = rest.find('>').unwrap_or(rest.len());
+        let tag = &rest[..end];
+        rest = &rest[(end + 1).min(rest.len())..];
+        if tag.starts_with(['/', '!', '?']) {
+            continue;
+        }
+        let tag = tag.trim_end_matches('/');
+        let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+        let mut element = Element {
+            name: &tag[..name_end],
+            attrs: Vec::new(),
+        };
+        let mut attrs = &tag[name_end..];
+        while let Some(eq) = attrs.find('=') {
+            let name = attrs[..eq].trim();
+            let value = attrs[eq + 1..].trim_start();
+            let Some(quote) = value.chars().next().filter(|q| *q == '"' || *q == '\'') else {
+                break;
+            };
+            let value_end = value[1..].find(quote).map_or(value.len(), |i| i + 1);
+            let raw = &value[1..value_end];
+            // Declaration order in a style does not matter
+            let normalized = if name == "style" {
+                let mut decls: Vec<&str> = raw
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|d| !d.is_empty())
+                    .collect();
+                decls.sort_unstable();
+                decls.join(";")
+            } else {
+                raw.to_string()
+            };
+            element.attrs.push((name, normalized));
+            attrs = &value[(value_end + 1).min(value.len())..];
+        }
+        // pikru writes spaces in text as no-break spaces, C as plain spaces
+        let text = rest[..rest.find('<').unwrap_or(rest.len())].replace('\u{a0}', " ");
+        if !text.trim().is_empty() {
+            element.attrs.push(("#text", text.trim().to_string()));
+        }
+        elements.push(element);
+    }
+    elements
+}
+
+/// Whether two attribute values match, with numbers compared within [`FLOAT_TOLERANCE`]
+fn same_attr_value(a: &str, b: &str) -> bool {
+    fn tokens(s: &str) -> Vec<&str> {
+        s.split(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == 'e'))
+            .filter(|t| !t.is_empty())
+            .collect()
+    }
+    fn skeleton(s: &str) -> String {
+        s.chars()
+            .filter(|c| !(c.is_ascii_digit() || *c == '.' || *c == '-'))
+            .collect()
+    }
+    if a == b {
+        return true;
+    }
+    let (ta, tb) = (tokens(a), tokens(b));
+    skeleton(a) == skeleton(b)
+        && ta.len() == tb.len()
+        && ta
+            .iter()
+            .zip(&tb)
+            .all(|(x, y)| match (x.parse::<f64>(), y.parse::<f64>()) {
+                (Ok(x), Ok(y)) => (x - y).abs() <= FLOAT_TOLERANCE,
+                _ => x == y,
+            })
+}
+
+/// One-line summary of the first element where two SVGs differ, such as
+/// `path[2] d: C=M2,2L74,2 Rust=M2,2L110,2` (elements counted per name from 1).
+/// Returns `None` when every element and attribute matches within tolerance.
+///
+/// On the root `<svg>` only the viewport is compared, since C adds fixed
+/// attributes (`class`, `style`, `data-pikchr-date`) that do not affect rendering.
+pub fn first_difference(c_svg: &str, rust_svg: &str) -> Option<String> {
+    let c_svg = normalize_opacity(&normalize_html_entities(c_svg));
+    let rust_svg = normalize_opacity(&normalize_html_entities(rust_svg));
+    let c_elements = svg_elements(&c_svg);
+    let rust_elements = svg_elements(&rust_svg);
+
+    // `rect[2]` is the second rect of the document, not the second element
+    let label = |elements: &[Element], i: usize| {
+        let name = elements[i].name;
+        let n = elements[..=i].iter().filter(|e| e.name == name).count();
+        format!("{}[{}]", name, n)
+    };
+
+    for i in 0..c_elements.len().max(rust_elements.len()) {
+        let (c, rust) = match (c_elements.get(i), rust_elements.get(i)) {
+            (Some(c), Some(rust)) => (c, rust),
+            (Some(_), None) => return Some(format!("{}: only in C", label(&c_elements, i))),
+            (None, Some(_)) => {
+                return Some(format!("{}: only in Rust", label(&rust_elements, i)));
+            }
+            (None, None) => unreachable!(),
+        };
+        if c.name != rust.name {
+            return Some(format!(
+                "element {}: C=<{}> Rust=<{}>",
+                i + 1,
+                c.name,
+                rust.name
+            ));
+        }
+        let element = label(&c_elements, i);
+        let compared =
+            |attr: &str| c.name != "svg" || matches!(attr, "viewBox" | "width" | "height");
+        let value = |e: &Element, attr: &str| {
+            e.attrs
+                .iter()
+                .find(|(n, _)| *n == attr)
+                .map(|(_, v)| v.clone())
+        };
+        let names = c.attrs.iter().chain(&rust.attrs).map(|(n, _)| *n);
+        for attr in names.filter(|n| compared(n)) {
+            let (cv, rv) = (value(c, attr), value(rust, attr));
+            let same = match (&cv, &rv) {
+                (Some(cv), Some(rv)) => same_attr_value(cv, rv),
+                (None, None) => true,
+                _ => false,
+            };
+            if !same {
+                let show = |v: Option<String>| v.unwrap_or_else(|| "(none)".to_string());
+                return Some(format!(
+                    "{} {}: C={} Rust={}",
+                    element,
+                    attr,
+                    show(cv),
+                    show(rv)
+                ));
+            }
+        }
+    }
+    None
+}
+
+/// Render SVG to a pixel buffer using resvg, fitting the longer side to `size` pixels
+/// but scaling up at most 2x, so small diagrams compare at their natural detail
+fn render_svg_to_pixels(svg_content: &str, size: u32) -> Result<image::RgbaImage, String> {
+    rasterize_svg(svg_content, size, 2.0)
+}
+
+/// Render SVG to a pixel buffer using resvg, fitting the longer side to `size` pixels
+/// with a scale of at most `max_scale`
+fn rasterize_svg(svg_content: &str, size: u32, max_scale: f32) -> Result<image::RgbaImage, String> {
     // Normalize HTML entities to Unicode
-    let normalized = normalize_html_entities(svg_content);
+    let normalized = normalize_opacity(&normalize_html_entities(svg_content));
 
     // Parse SVG with usvg
     let options = usvg::Options::default();
     let tree = usvg::Tree::from_str(&normalized, &options)
         .map_err(|e| format!("Failed to parse SVG: {}", e))?;
 
-    // Get the SVG size and calculate scale to fit RENDER_SIZE
+    // Get the SVG size and calculate scale to fit the requested size
     let svg_size = tree.size();
-    let scale = (RENDER_SIZE as f32 / svg_size.width().max(svg_size.height())).min(2.0);
+    let scale = (size as f32 / svg_size.width().max(svg_size.height())).min(max_scale);
 
     let width = (svg_size.width() * scale).ceil() as u32;
     let height = (svg_size.height() * scale).ceil() as u32;
@@ -200,6 +420,21 @@ fn render_svg_to_pixels(svg_content: &str) -> Result<image::RgbaImage, String> {
     Ok(img)
 }
 
+/// Rasterize pikchr output to PNG bytes.
+///
+/// Uses the same resvg pipeline as the visual comparison, with the longer side
+/// fitted to exactly `size` pixels (see [`RENDER_SIZE`]). `output` is what
+/// `pikru::pikchr` returns; `print` text before the SVG is skipped.
+pub fn render_png(output: &str, size: u32) -> Result<Vec<u8>, String> {
+    let svg = extract_svg(output).ok_or_else(|| "pikchr produced no SVG".to_string())?;
+    let img = rasterize_svg(svg, size, f32::INFINITY)?;
+
+    let mut png = Vec::new();
+    img.write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode PNG: {}", e))?;
+    Ok(png)
+}
+
 /// Compare two images using SSIM and return the score (1.0 = identical, 0.0 = different)
 fn compare_images_ssim(img1: &image::RgbaImage, img2: &image::RgbaImage) -> Result<f64, String> {
     let (w1, h1) = img1.dimensions();
@@ -237,10 +472,8 @@ fn compare_images_ssim(img1: &image::RgbaImage, img2: &image::RgbaImage) -> Resu
 pub fn compare_outputs(c_output: &str, rust_output: &str, rust_is_err: bool) -> CompareResult {
     let c_is_error = c_output.contains("ERROR:");
     let c_has_svg = c_output.contains("<svg");
-    let c_has_comment = c_output.contains("<!--");
 
     let rust_has_svg = rust_output.contains("<svg");
-    let rust_has_comment = rust_output.contains("<!--");
 
     // Handle error cases
     if rust_is_err {
@@ -266,9 +499,11 @@ pub fn compare_outputs(c_output: &str, rust_output: &str, rust_is_err: bool) ->
         };
     }
 
-    // Neither errored - compare outputs
-    if !c_has_svg && !rust_has_svg && (c_has_comment || rust_has_comment) {
-        if c_output.trim() == rust_output.trim() {
+    // Neither errored - compare outputs.
+    // Without SVG on either side (print-only output or the empty-diagram comment)
+    // there is nothing to render, so compare the normalized text.
+    if !c_has_svg && !rust_has_svg {
+        if normalize_non_svg_text(c_output) == normalize_non_svg_text(rust_output) {
             return CompareResult::NonSvgMatch;
         } else {
             return CompareResult::NonSvgMismatch {
@@ -298,7 +533,7 @@ pub fn compare_outputs(c_output: &str, rust_output: &str, rust_is_err: bool) ->
     };
 
     // Try visual comparison first
-    let c_img = match render_svg_to_pixels(c_svg) {
+    let c_img = match render_svg_to_pixels(c_svg, RENDER_SIZE) {
         Ok(img) => img,
         Err(e) => {
             return CompareResult::RenderError {
@@ -307,7 +542,7 @@ pub fn compare_outputs(c_output: &str, rust_output: &str, rust_is_err: bool) ->
         }
     };
 
-    let rust_img = match render_svg_to_pixels(rust_svg) {
+    let rust_img = match render_svg_to_pixels(rust_svg, RENDER_SIZE) {
         Ok(img) => img,
         Err(e) => {
             return CompareResult::RenderError {
@@ -331,7 +566,10 @@ pub fn compare_outputs(c_output: &str, rust_output: &str, rust_is_err: bool) ->
     }
 
     // Visual comparison failed - get structural diff for details
-    let details = match (parse_svg(c_output), parse_svg(rust_output)) {
+    let details = match (
+        parse_svg(&normalize_opacity(c_output)),
+        parse_svg(&normalize_opacity(rust_output)),
+    ) {
         (Ok(c_parsed), Ok(rust_parsed)) => {
             match check_same_with_report(&c_parsed, &rust_parsed, svg_compare_options()) {
                 SameReport::Same => {
@@ -347,7 +585,11 @@ pub fn compare_outputs(c_output: &str, rust_output: &str, rust_is_err: bool) ->
         (_, Err(e)) => format!("Failed to parse Rust SVG for diff: {}", e),
     };
 
-    CompareResult::SvgMismatch { ssim, details }
+    CompareResult::SvgMismatch {
+        ssim,
+        details,
+        first_difference: first_difference(c_svg, rust_svg),
+    }
 }
 
 /// Write debug SVGs for a test so we can inspect C vs Rust output.
@@ -397,3 +639,111 @@ pub fn run_c_pikchr(c_pikchr_path: &Utf8Path, source: &str) -> String {
         .expect("failed to wait on C pikchr");
     String::from_utf8_lossy(&output.stdout).to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_png_fits_longer_side() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 40 20">
+<rect x="2" y="2" width="36" height="16" style="fill:none;stroke:black" />
+</svg>"#;
+        let png = render_png(&format!("1<br>\n{}", svg), 800).expect("render failed");
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        // IHDR width and height, big-endian; small diagrams are scaled up to `size`
+        let dimension = |at: usize| u32::from_be_bytes(png[at..at + 4].try_into().unwrap());
+        assert_eq!((dimension(16), dimension(20)), (800, 400));
+    }
+
+    #[test]
+    fn compare_reports_first_difference() {
+        let svg = |width: &str| {
+            format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 300 100">
+<rect x="2" y="2" width="72" height="72" style="fill:none;stroke:black" />
+<rect x="100" y="2" width="{}" height="72" style="fill:none;stroke:black" />
+<text x="38" y="38">a&nbsp;b</text>
+</svg>"#,
+                width
+            )
+        };
+        let c = svg("72");
+
+        // Within tolerance, and no-break spaces in text count as spaces
+        assert_eq!(first_difference(&c, &svg("72.001")), None);
+        assert_eq!(first_difference(&c, &c.replace("&nbsp;", "\u{a0}")), None);
+        assert_eq!(
+            first_difference(&c, &svg("108")).as_deref(),
+            Some("rect[2] width: C=72 Rust=108")
+        );
+        assert_eq!(
+            first_difference(&c, &c.replace(">a&nbsp;b<", ">ab<")).as_deref(),
+            Some("text[1] #text: C=a b Rust=ab")
+        );
+        assert_eq!(
+            first_difference(&c, &c.replace("<text", "<circle r=\"1\" /><text")).as_deref(),
+            Some("element 4: C=<text> Rust=<circle>")
+        );
+
+        match compare_outputs(&c, &svg("108"), false) {
+            CompareResult::SvgMismatch {
+                first_difference, ..
+            } => assert_eq!(
+                first_difference.as_deref(),
+                Some("rect[2] width: C=72 Rust=108")
+            ),
+            other => panic!("expected an SVG mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn compare_normalizes_fill_opacity() {
+        let svg = |style: &str| {
+            format!(
+                r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+<path d="M2,2L74,2L74,74L2,74Z" style="{}" />
+</svg>"#,
+                style
+            )
+        };
+        let rgba = svg("fill:rgba(255,0,0,0.5);stroke:rgb(0,0,0);");
+        let opacity = svg("fill:rgb(255,0,0);fill-opacity:0.5;stroke:rgb(0,0,0);");
+
+        assert_eq!(first_difference(&rgba, &opacity), None);
+        assert_eq!(
+            first_difference(
+                &rgba,
+                &svg("stroke:rgb(0,0,0);fill-opacity:0.5;fill:rgb(255,0,0)")
+            ),
+            None
+        );
+        assert!(first_difference(&rgba, &opacity.replace("0.5", "0.25")).is_some());
+        assert!(matches!(
+            compare_outputs(&rgba, &opacity, false),
+            CompareResult::Match
+        ));
+    }
+
+    #[test]
+    fn compare_print_only_output_normalizes_text() {
+        let c = "13<br>\n0.5<br>\n";
+        for rust in ["13<br>\n0.5<br>  \n\n", "13\n0.5<br>"] {
+            let result = compare_outputs(c, rust, false);
+            assert!(
+                matches!(result, CompareResult::NonSvgMatch),
+                "{:?}: {:?}",
+                rust,
+                result
+            );
+        }
+
+        let result = compare_outputs(c, "13<br>\n0.6<br>\n", false);
+        assert!(
+            matches!(result, CompareResult::NonSvgMismatch { .. }),
+            "{:?}",
+            result
+        );
+    }
+}
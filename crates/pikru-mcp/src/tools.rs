@@ -28,6 +28,22 @@ pub struct DebugTestParams {
     pub filter: Option<String>,
 }
 
+/// Parameters for rendering pikchr source to a PNG preview
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct RenderPngParams {
+    /// Pikchr source to render
+    pub source: String,
+    /// Size in pixels of the longer side of the image (defaults to 800)
+    pub size: Option<u32>,
+}
+
+/// Parameters for dumping the parsed AST of pikchr source
+#[derive(Debug, Deserialize, Serialize, JsonSchema)]
+pub struct ParseAstParams {
+    /// Pikchr source to parse
+    pub source: String,
+}
+
 /// Paths to project resources
 pub struct PikruPaths {
     pub project_root: PathBuf,
@@ -455,6 +471,51 @@ impl PikruServer {
             ))])),
         }
     }
+
+    /// Render pikchr source to a PNG preview
+    #[tool(
+        description = "Render pikchr source with pikru and return a PNG preview. Parse and render errors are returned as diagnostic text."
+    )]
+    async fn render_png(
+        &self,
+        Parameters(params): Parameters<RenderPngParams>,
+    ) -> Result<CallToolResult, McpError> {
+        let size = params.size.unwrap_or(pikru_compare::RENDER_SIZE);
+
+        let png = pikru::pikchr(&params.source)
+            .map_err(|e| e.to_string())
+            .and_then(|svg| pikru_compare::render_png(&svg, size));
+        match png {
+            Ok(png) => {
+                let b64 = base64::engine::general_purpose::STANDARD.encode(&png);
+                Ok(CallToolResult::success(vec![Content::image(
+                    b64,
+                    "image/png",
+                )]))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(strip_ansi(&e))])),
+        }
+    }
+
+    /// Parse pikchr source and return the AST as JSON
+    #[tool(
+        description = "Parse pikchr source with pikru and return the AST as JSON, before macro expansion and rendering. Useful to tell parser bugs from renderer bugs. Parse errors are returned with source spans."
+    )]
+    async fn parse_pikchr_ast(
+        &self,
+        Parameters(params): Parameters<ParseAstParams>,
+    ) -> Result<CallToolResult, McpError> {
+        match pikru::parse::parse(&params.source) {
+            Ok(program) => {
+                let json = facet_json::to_string(&program)
+                    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+                Ok(CallToolResult::success(vec![Content::text(json)]))
+            }
+            Err(e) => Ok(CallToolResult::error(vec![Content::text(strip_ansi(
+                &e.to_report("<input>", &params.source),
+            ))])),
+        }
+    }
 }
 
 #[tool_handler(router = self.tool_router)]
@@ -465,7 +526,7 @@ impl ServerHandler for PikruServer {
             capabilities: ServerCapabilities::builder().enable_tools().build(),
             server_info: rmcp::model::Implementation::from_build_env(),
             instructions: Some(
-                "Run pikchr compliance tests comparing C and Rust implementations".into(),
+                "Run pikchr compliance tests comparing C and Rust implementations, and render pikchr source to PNG previews".into(),
             ),
         }
     }
@@ -1,18 +1,25 @@
 //! Abstract Syntax Tree types for pikchr
 //!
 //! These types represent the parsed structure of a pikchr diagram.
+//! With the `facet` feature they derive `Facet`, so tools can dump a parsed program
+//! (e.g. as JSON) for debugging.
 
-use crate::types::{Angle, Length, OffsetIn, UnitVec};
+use crate::types::{Angle, Length, OffsetIn, Span, UnitVec};
+#[cfg(feature = "facet")]
+use facet::Facet;
 use glam::DVec2;
 
 /// A complete pikchr program
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct Program {
     pub statements: Vec<Statement>,
 }
 
 /// A pikchr statement
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum Statement {
     /// Direction change: up, down, left, right
     Direction(Direction),
@@ -28,6 +35,10 @@ pub enum Statement {
     Print(Print),
     /// Error statement: error "message" - produces an error
     Error(ErrorStmt),
+    /// Reset statement: reset - restores built-in variables to their defaults
+    Reset,
+    /// Include statement: include "other.pikchr" (pikru extension)
+    Include(IncludeStmt),
     /// Labeled statement: A: box "hello"
     Labeled(LabeledStatement),
     /// Object statement: box "hello" width 2
@@ -36,12 +47,23 @@ pub enum Statement {
 
 /// Error statement - produces an intentional error
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct ErrorStmt {
     pub message: String,
 }
 
+/// Include statement - splices in the statements of another file
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+pub struct IncludeStmt {
+    /// Path as written, relative to the including file
+    pub path: String,
+}
+
 /// Direction: up, down, left, right
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum Direction {
     Up,
     Down,
@@ -138,14 +160,19 @@ impl Direction {
 
 /// Variable assignment
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct Assignment {
     pub lvalue: LValue,
     pub op: AssignOp,
     pub rvalue: RValue,
+    /// Source location of the whole assignment, for diagnostics
+    pub span: Span,
 }
 
 /// Left-hand side of assignment
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum LValue {
     Variable(String),
     Fill,
@@ -155,6 +182,8 @@ pub enum LValue {
 
 /// Assignment operator
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum AssignOp {
     Assign,    // =
     AddAssign, // +=
@@ -165,6 +194,8 @@ pub enum AssignOp {
 
 /// Right-hand side of assignment
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum RValue {
     Expr(Expr),
     PlaceName(String), // Color names like Red, Blue
@@ -172,6 +203,7 @@ pub enum RValue {
 
 /// Macro definition
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct Define {
     pub name: String,
     pub body: String, // Raw code block content
@@ -179,27 +211,24 @@ pub struct Define {
 
 /// Macro invocation
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct MacroCall {
     pub name: String,
-    pub args: Vec<MacroArg>,
-}
-
-/// Macro argument
-#[derive(Debug, Clone)]
-pub enum MacroArg {
-    String(String),
-    Expr(Expr),
-    Ident(String),
+    /// Arguments as written, trimmed; they replace `$1`..`$9` in the body as text
+    pub args: Vec<String>,
 }
 
 /// Assert statement
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct Assert {
     pub condition: AssertCondition,
 }
 
 /// Assert condition
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum AssertCondition {
     ExprEqual(Expr, Expr),
     PositionEqual(Box<Position>, Box<Position>),
@@ -207,12 +236,15 @@ pub enum AssertCondition {
 
 /// Print statement
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct Print {
     pub args: Vec<PrintArg>,
 }
 
 /// Print argument
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum PrintArg {
     String(String),
     Expr(Expr),
@@ -221,6 +253,7 @@ pub enum PrintArg {
 
 /// Labeled statement: A: box or A: position
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct LabeledStatement {
     pub label: String,
     pub content: LabeledContent,
@@ -228,6 +261,8 @@ pub struct LabeledStatement {
 
 /// Content after a label
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum LabeledContent {
     Position(Position),
     Object(ObjectStatement),
@@ -235,6 +270,7 @@ pub enum LabeledContent {
 
 /// Object statement: basetype with attributes
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct ObjectStatement {
     pub basetype: BaseType,
     pub attributes: Vec<Attribute>,
@@ -242,6 +278,8 @@ pub struct ObjectStatement {
 
 /// Base type of an object
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum BaseType {
     /// Primitive class: box, circle, line, arrow, etc.
     Class(ClassName),
@@ -253,6 +291,8 @@ pub enum BaseType {
 
 /// Primitive class names
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum ClassName {
     Arc,
     Arrow,
@@ -273,6 +313,8 @@ pub enum ClassName {
 
 /// Object attribute
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum Attribute {
     /// Numeric property: width 2, height 3cm
     NumProperty(NumProperty, RelExpr),
@@ -320,6 +362,8 @@ pub enum Attribute {
 
 /// Then clause content
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum ThenClause {
     To(Position),
     DirectionEven(Direction, Position),
@@ -331,6 +375,8 @@ pub enum ThenClause {
 
 /// Numeric property names
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum NumProperty {
     Height,
     Width,
@@ -341,6 +387,8 @@ pub enum NumProperty {
 
 /// Dash property names
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum DashProperty {
     Dotted,
     Dashed,
@@ -348,6 +396,8 @@ pub enum DashProperty {
 
 /// Color property names
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum ColorProperty {
     Fill,
     Color,
@@ -356,14 +406,20 @@ pub enum ColorProperty {
 /// A property reference for dot-property reads (object.property)
 /// Wraps all three property types so expressions can read any of them.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum PropertyRef {
     Num(NumProperty),
     Dash(DashProperty),
     Color(ColorProperty),
+    /// Number of vertices of a line-like object, 0 for other shapes (pikru extension)
+    NPoints,
 }
 
 /// Boolean property values
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum BoolProperty {
     Clockwise,
     CounterClockwise,
@@ -378,6 +434,7 @@ pub enum BoolProperty {
 
 /// With clause: .edge at position
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct WithClause {
     pub edge: WithEdge,
     pub position: Position,
@@ -385,6 +442,8 @@ pub struct WithClause {
 
 /// Edge specification in with clause
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum WithEdge {
     DotEdge(EdgePoint),
     EdgePoint(EdgePoint),
@@ -392,15 +451,20 @@ pub enum WithEdge {
 
 /// Text position attributes
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct TextPosition {
     pub attrs: Vec<TextAttr>,
 }
 
 /// Text attribute
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum TextAttr {
-    Above,
-    Below,
+    /// `above`, with an optional extra distance (`"x" above 0.2`), evaluated at render time
+    Above(Option<Expr>),
+    /// `below`, with an optional extra distance (`"x" below 0.2`), evaluated at render time
+    Below(Option<Expr>),
     Center,
     LJust,
     RJust,
@@ -414,6 +478,7 @@ pub enum TextAttr {
 
 /// A relative expression (expr with optional %)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct RelExpr {
     pub expr: Expr,
     pub is_percent: bool,
@@ -421,6 +486,8 @@ pub struct RelExpr {
 
 /// Expression
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum Expr {
     Number(f64), // stored in inches already
     Variable(String),
@@ -429,16 +496,22 @@ pub enum Expr {
     BuiltinVar(BuiltinVar),
     FuncCall(FuncCall),
     DistCall(Box<Position>, Box<Position>),
+    /// Number of objects placed so far, optionally of one class: `count(box)` (pikru extension)
+    CountCall(Option<NthClass>),
     ObjectProp(Object, PropertyRef),
     ObjectCoord(Object, Coord),
     ObjectEdgeCoord(Object, EdgePoint, Coord),
     VertexCoord(Nth, Object, Coord),
+    /// Coordinate of a parenthesized position: `(A.ne + (1,0)).x` (pikru extension)
+    PositionCoord(Box<Position>, Coord),
     BinaryOp(Box<Expr>, BinaryOp, Box<Expr>),
     UnaryOp(UnaryOp, Box<Expr>),
 }
 
 /// Built-in variables
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum BuiltinVar {
     Fill,
     Color,
@@ -447,6 +520,8 @@ pub enum BuiltinVar {
 
 /// Coordinate: x or y
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum Coord {
     X,
     Y,
@@ -454,6 +529,7 @@ pub enum Coord {
 
 /// Function call
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct FuncCall {
     pub func: Function,
     pub args: Vec<Expr>,
@@ -461,18 +537,28 @@ pub struct FuncCall {
 
 /// Built-in functions
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum Function {
     Abs,
     Cos,
     Sin,
     Int,
     Sqrt,
+    /// Natural exponential (pikru extension)
+    Exp,
+    /// Natural logarithm (pikru extension)
+    Log,
     Max,
     Min,
+    /// `atan2(y, x)` in degrees (pikru extension)
+    Atan2,
 }
 
 /// Binary operator
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum BinaryOp {
     Add,
     Sub,
@@ -482,6 +568,8 @@ pub enum BinaryOp {
 
 /// Unary operator
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum UnaryOp {
     Neg,
     Pos,
@@ -489,6 +577,8 @@ pub enum UnaryOp {
 
 /// Position
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum Position {
     /// (x, y) coordinate pair
     Coords(Expr, Expr),
@@ -500,7 +590,8 @@ pub enum Position {
     PlaceOffset(Place, BinaryOp, Expr, Expr),
     /// Between positions: 0.5 between A and B
     Between(Expr, Box<Position>, Box<Position>),
-    /// Angle bracket: 0.5 <A, B>
+    /// Angle bracket: 0.5 <A, B>. Same point as `Between`; kept apart so the AST
+    /// records which spelling the source used.
     Bracket(Expr, Box<Position>, Box<Position>),
     /// Above/below: 1cm above B
     AboveBelow(Expr, AboveBelow, Box<Position>),
@@ -514,6 +605,8 @@ pub enum Position {
 
 /// Above or below
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum AboveBelow {
     Above,
     Below,
@@ -521,6 +614,8 @@ pub enum AboveBelow {
 
 /// Left or right
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum LeftRight {
     Left,
     Right,
@@ -528,6 +623,8 @@ pub enum LeftRight {
 
 /// Heading direction
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum HeadingDir {
     EdgePoint(EdgePoint),
     Expr(Expr),
@@ -535,6 +632,8 @@ pub enum HeadingDir {
 
 /// Place - a reference to a location
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum Place {
     /// Vertex of object: 2nd vertex of spline
     Vertex(Nth, Object),
@@ -548,6 +647,8 @@ pub enum Place {
 
 /// Object reference
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum Object {
     /// Named object: B, Main.Sub
     Named(ObjectName),
@@ -559,6 +660,7 @@ pub enum Object {
 
 /// Named object
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct ObjectName {
     pub base: ObjectNameBase,
     pub path: Vec<String>, // dot-separated path
@@ -566,6 +668,8 @@ pub struct ObjectName {
 
 /// Base of object name
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum ObjectNameBase {
     This,
     PlaceName(String),
@@ -573,6 +677,8 @@ pub enum ObjectNameBase {
 
 /// Modifier for ordinal references (2nd last box, 2nd previous box)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum NthModifier {
     None,
     Last,
@@ -581,6 +687,8 @@ pub enum NthModifier {
 
 /// Nth reference
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum Nth {
     /// Ordinal: 1st, 2nd, 3rd, etc. with optional last/previous modifier
     Ordinal(u32, NthModifier, Option<NthClass>),
@@ -594,6 +702,8 @@ pub enum Nth {
 
 /// Class for nth reference
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum NthClass {
     ClassName(ClassName),
     Sublist,
@@ -601,6 +711,8 @@ pub enum NthClass {
 
 /// Edge point names
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "facet", derive(Facet))]
+#[cfg_attr(feature = "facet", repr(u8))]
 pub enum EdgePoint {
     North,
     South,
@@ -661,6 +773,7 @@ impl EdgePoint {
 
 /// String literal
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct StringLit {
     pub value: String,
 }
@@ -723,6 +836,16 @@ mod tests {
         assert_eq!(d.y, -1.0);
     }
 
+    #[test]
+    #[cfg(feature = "facet")]
+    fn test_program_serializes_to_json() {
+        let program = crate::parse::parse("A: box \"hi\"\narrow from A.e right 1").unwrap();
+        let json = facet_json::to_string(&program).unwrap();
+        assert!(json.contains("\"Labeled\""), "{}", json);
+        assert!(json.contains("\"hi\""), "{}", json);
+        assert!(json.contains("\"Arrow\""), "{}", json);
+    }
+
     #[test]
     fn test_direction_opposite() {
         assert_eq!(Direction::Right.opposite(), Direction::Left);
@@ -42,6 +42,14 @@ pub enum ParseError {
 
     #[error("unknown keyword: {keyword}")]
     UnknownKeyword { keyword: String, span: Span },
+
+    /// `"%g" % expr` - C pikchr has no string formatting, labels are literal text
+    #[error("string formatting is not supported")]
+    UnsupportedStringFormat { span: Span },
+
+    /// `rgb(...)`/`hsl(...)` with an argument out of range
+    #[error("invalid color: {color}")]
+    InvalidColor { color: String, span: Span },
 }
 
 // ============================================================================
@@ -96,6 +104,9 @@ pub enum EvalError {
 
     #[error("cannot reference 'this' outside object definition")]
     NoThis { span: Span },
+
+    #[error("unknown color: {name}")]
+    UnknownColor { name: String, span: Span },
 }
 
 // ============================================================================
@@ -215,6 +226,26 @@ impl PikruError {
                         )
                         .finish()
                 }
+                ParseError::InvalidColor { color, span } => {
+                    Report::build(ReportKind::Error, to_range(span))
+                        .with_message(format!("invalid color: {}", color))
+                        .with_label(
+                            Label::new(to_range(span))
+                                .with_message("argument out of range")
+                                .with_color(Color::Red),
+                        )
+                        .finish()
+                }
+                ParseError::UnsupportedStringFormat { span } => {
+                    Report::build(ReportKind::Error, to_range(span))
+                        .with_message("string formatting is not supported")
+                        .with_label(
+                            Label::new(to_range(span))
+                                .with_message("labels are literal text; `%` cannot format them")
+                                .with_color(Color::Red),
+                        )
+                        .finish()
+                }
             },
             PikruError::Eval(e) => match e {
                 EvalError::UndefinedVariable {
@@ -333,6 +364,16 @@ impl PikruError {
                     )
                     .with_help("create at least one object before using 'previous'")
                     .finish(),
+                EvalError::UnknownColor { name, span } => {
+                    Report::build(ReportKind::Error, to_range(span))
+                        .with_message(format!("unknown color: {}", name))
+                        .with_label(
+                            Label::new(to_range(span))
+                                .with_message("not a color name")
+                                .with_color(Color::Red),
+                        )
+                        .finish()
+                }
                 EvalError::NoThis { span } => Report::build(ReportKind::Error, to_range(span))
                     .with_message("cannot reference 'this' outside object definition")
                     .with_label(
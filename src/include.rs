@@ -0,0 +1,87 @@
+//! Include resolution for pikchr (pikru extension)
+//!
+//! Replaces `include "file"` statements with the statements of that file, read
+//! relative to the including file. C pikchr has no include mechanism.
+
+use crate::ast::*;
+use crate::errors::PikruError;
+use crate::parse;
+use std::path::{Path, PathBuf};
+
+/// Splice every `include` in `program`, which was read from `path`
+pub fn resolve_includes(program: Program, path: &Path) -> Result<Program, PikruError> {
+    let mut stack = vec![canonical(path)?];
+    let statements = resolve_statements(program.statements, path, &mut stack)?;
+    Ok(Program { statements })
+}
+
+/// Resolve the includes in `statements` from the file at `path`. `stack` holds the
+/// files being included, outermost first, to catch cycles.
+fn resolve_statements(
+    statements: Vec<Statement>,
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<Vec<Statement>, PikruError> {
+    let mut output = Vec::with_capacity(statements.len());
+    for stmt in statements {
+        match stmt {
+            Statement::Include(include) => {
+                let dir = path.parent().unwrap_or(Path::new(""));
+                let included = dir.join(&include.path);
+                let key = canonical(&included)?;
+                if stack.contains(&key) {
+                    let chain: Vec<String> = stack
+                        .iter()
+                        .chain([&key])
+                        .map(|p| p.display().to_string())
+                        .collect();
+                    return Err(PikruError::Generic(format!(
+                        "include cycle: {}",
+                        chain.join(" -> ")
+                    )));
+                }
+
+                let source = std::fs::read_to_string(&included).map_err(|e| {
+                    PikruError::Generic(format!("cannot include {}: {}", included.display(), e))
+                })?;
+                let program = parse::parse(&source).map_err(|e| {
+                    PikruError::Generic(format!("in {}: {}", included.display(), e))
+                })?;
+                stack.push(key);
+                output.extend(resolve_statements(program.statements, &included, stack)?);
+                stack.pop();
+            }
+            Statement::Object(obj) => {
+                output.push(Statement::Object(resolve_object(obj, path, stack)?));
+            }
+            Statement::Labeled(LabeledStatement {
+                label,
+                content: LabeledContent::Object(obj),
+            }) => {
+                output.push(Statement::Labeled(LabeledStatement {
+                    label,
+                    content: LabeledContent::Object(resolve_object(obj, path, stack)?),
+                }));
+            }
+            other => output.push(other),
+        }
+    }
+    Ok(output)
+}
+
+/// Resolve the includes inside a sublist object
+fn resolve_object(
+    mut obj: ObjectStatement,
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+) -> Result<ObjectStatement, PikruError> {
+    if let BaseType::Sublist(statements) = obj.basetype {
+        obj.basetype = BaseType::Sublist(resolve_statements(statements, path, stack)?);
+    }
+    Ok(obj)
+}
+
+fn canonical(path: &Path) -> Result<PathBuf, PikruError> {
+    path.canonicalize()
+        .map_err(|e| PikruError::Generic(format!("cannot include {}: {}", path.display(), e)))
+}
@@ -4,13 +4,14 @@ use pest_derive::Parser;
 
 pub mod ast;
 pub mod errors;
+pub mod include;
 pub(crate) mod log;
 pub mod macros;
 pub mod parse;
 pub mod render;
 pub mod types;
 
-pub use render::RenderOptions;
+pub use render::{CompatLevel, RenderOptions};
 
 #[derive(Parser)]
 #[grammar = "pikchr.pest"]
@@ -65,12 +66,78 @@ pub fn pikchr_with_options(source: &str, options: &RenderOptions) -> Result<Stri
     })
 }
 
+/// Render the pikchr file at `path` to SVG.
+///
+/// With [`RenderOptions::resolve_includes`], `include "other.pikchr"` statements are
+/// replaced by the statements of that file, read relative to the including file.
+/// Including a file that is already being included is an error.
+pub fn pikchr_from_path(
+    path: impl AsRef<std::path::Path>,
+    options: &RenderOptions,
+) -> Result<String, String> {
+    use errors::PikruError;
+
+    let path = path.as_ref();
+    let name = path.display().to_string();
+    let source = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", name, e))?;
+
+    let program = parse::parse(&source).map_err(|e| {
+        let err: PikruError = e;
+        err.to_report(&name, &source)
+    })?;
+
+    let program = if options.resolve_includes {
+        include::resolve_includes(program, path).map_err(|e| e.to_report(&name, &source))?
+    } else {
+        program
+    };
+
+    let program = macros::expand_macros(program).map_err(|e| {
+        let err: PikruError = e;
+        err.to_report(&name, &source)
+    })?;
+
+    render::render_with_options(&program, options).map_err(|e| {
+        let err: PikruError = e;
+        err.to_report(&name, &source)
+    })
+}
+
+/// Render pikchr source to gzip-compressed SVG, ready to serve as `.svgz`
+/// (or with `Content-Encoding: gzip`). Requires the `svgz` feature.
+#[cfg(feature = "svgz")]
+pub fn pikchr_to_svgz(source: &str) -> Result<Vec<u8>, String> {
+    use flate2::{Compression, write::GzEncoder};
+    use std::io::Write;
+
+    let svg = pikchr(source)?;
+    let gzip_error = |e: std::io::Error| format!("gzip compression failed: {}", e);
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(svg.as_bytes()).map_err(gzip_error)?;
+    encoder.finish().map_err(gzip_error)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use facet_svg::facet_xml::SerializeOptions;
     use pest::Parser;
 
+    /// Whether some element's style has exactly the declarations of `expected`, in any
+    /// order. pikru orders them `fill;stroke;stroke-width`, C `fill;stroke-width;stroke`.
+    fn has_style(svg: &str, expected: &str) -> bool {
+        fn declarations(style: &str) -> Vec<&str> {
+            let mut decls: Vec<&str> = style.split(';').filter(|d| !d.is_empty()).collect();
+            decls.sort_unstable();
+            decls
+        }
+        let expected = declarations(expected);
+        svg.split(" style=\"")
+            .skip(1)
+            .filter_map(|rest| rest.split_once('"'))
+            .any(|(style, _)| declarations(style) == expected)
+    }
+
     #[test]
     fn parse_simple_box() {
         let input = r#"box "Hello""#;
@@ -144,6 +211,24 @@ mod tests {
         assert!(result.is_ok(), "Failed to parse: {:?}", result.err());
     }
 
+    #[test]
+    fn parse_position_dot_x() {
+        // Coordinates of arbitrary parenthesized positions (pikru extension)
+        for input in [
+            "box at (A.ne).x, (1st box).y",
+            "box at (A.ne + (1, 0)).x, (0.5 between A and B).y",
+            "box at ((1, 2)).x, (1 + 2)",
+        ] {
+            let result = PikchrParser::parse(Rule::program, input);
+            assert!(
+                result.is_ok(),
+                "Failed to parse {:?}: {:?}",
+                input,
+                result.err()
+            );
+        }
+    }
+
     #[test]
     fn parse_expr_edgept() {
         // Test "expr ne of position" style
@@ -533,6 +618,71 @@ mod tests {
         assert_eq!(program.statements.len(), 3);
     }
 
+    #[test]
+    fn ast_between_phrasings() {
+        use crate::ast::{Attribute, Position, Statement};
+
+        // cref: between (pikchr.y:734-736) - WAY BETWEEN | BETWEEN | OF THE WAY BETWEEN
+        let render = |at: &str| {
+            let input = format!("A: box\nB: box at (2, 1)\ncircle rad 0.1 at {}", at);
+            let program = crate::parse::parse(&input).expect("parse failed");
+            (program, crate::pikchr(&input).expect("render failed"))
+        };
+        let (_, expected) = render("0.25 between A and B");
+        for phrasing in [
+            "0.25 between A and B",
+            "0.25 way between A and B",
+            "0.25 of the way between A and B",
+        ] {
+            let (program, svg) = render(phrasing);
+            let Some(Statement::Object(circle)) = program.statements.last() else {
+                panic!("{:?}: last statement is not an object", phrasing);
+            };
+            assert!(
+                circle
+                    .attributes
+                    .iter()
+                    .any(|a| matches!(a, Attribute::At(Position::Between(..)))),
+                "{:?}: expected Position::Between, got {:?}",
+                phrasing,
+                circle.attributes
+            );
+            assert_eq!(svg, expected, "{:?}", phrasing);
+        }
+
+        // The angle-bracket form is a separate variant but lands on the same point
+        assert_eq!(render("0.25 <A, B>").1, expected);
+    }
+
+    #[test]
+    fn ast_bracket_position() {
+        use crate::ast::{Attribute, Position, Statement};
+
+        // cref: position (pikchr.y:717) - expr LT position COMMA position GT
+        let input = "A: box\nB: box at (2, 1)\ncircle rad 0.1 at 1/4 <A.n, B.s>";
+        let program = crate::parse::parse(input).expect("parse failed");
+        let Some(Statement::Object(circle)) = program.statements.last() else {
+            panic!("last statement is not an object");
+        };
+        assert!(
+            circle
+                .attributes
+                .iter()
+                .any(|a| matches!(a, Attribute::At(Position::Bracket(..)))),
+            "expected Position::Bracket, got {:?}",
+            circle.attributes
+        );
+
+        // A quarter of the way from A.n (0,0.25) to B.s (2,0.75); center from C
+        let svg = crate::pikchr(input).expect("render failed");
+        assert!(svg.contains("cx=\"128.16\" cy=\"128.16\""), "{}", svg);
+        assert_eq!(
+            svg,
+            crate::pikchr("A: box\nB: box at (2, 1)\ncircle rad 0.1 at 1/4 between A.n and B.s")
+                .expect("render failed")
+        );
+    }
+
     #[test]
     fn ast_test01_file() {
         let input = include_str!("../vendor/pikchr-c/tests/test01.pikchr");
@@ -663,6 +813,28 @@ mod tests {
         assert!(svg.contains("<circle"), "Should contain a circle");
     }
 
+    #[test]
+    fn render_is_reproducible() {
+        // pikru emits no <defs>, gradients or patterns, so there are no generated IDs;
+        // repeated renders must be byte-identical
+        let input = "fill = Bisque\nA: box \"a\" fill red\nB: circle \"b\" behind A\narrow from A to B chop\nS: [ dot; box ]";
+        let first = crate::pikchr(input).expect("render failed");
+        let second = crate::pikchr(input).expect("render failed");
+        assert_eq!(first, second);
+        assert!(!first.contains(" id="), "{}", first);
+        assert!(!first.contains("<defs"), "{}", first);
+
+        // Debug labels for named positions come out in a stable order
+        let input = "debug_label_color = red\nbox\nQ: 1 above last box\nP: 1 below last box\nR: 1 left of last box";
+        let first = crate::pikchr(input).expect("render failed");
+        assert_eq!(first, crate::pikchr(input).expect("render failed"));
+        let p = first.find(">P<").expect("P label missing");
+        let q = first.find(">Q<").expect("Q label missing");
+        let r = first.find(">R<").expect("R label missing");
+        // Definition order, like C
+        assert!(q < p && p < r, "{}", first);
+    }
+
     #[test]
     fn render_explicit_size() {
         use crate::render::{RenderOptions, render_with_options};
@@ -695,6 +867,1035 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_animate_reveal() {
+        let options = RenderOptions {
+            animate_reveal: true,
+            ..Default::default()
+        };
+        let svg = crate::pikchr_with_options("box \"a\"\narrow\ncircle", &options)
+            .expect("render failed");
+        assert!(svg.contains("@keyframes pik-reveal"), "{}", svg);
+
+        // Objects are staggered in placement order; text shares its object's delay
+        let delays: Vec<_> = svg
+            .match_indices("animation-delay:")
+            .map(|(i, _)| &svg[i..i + svg[i..].find(';').unwrap()])
+            .collect();
+        assert_eq!(
+            delays,
+            vec![
+                "animation-delay:0s",   // box
+                "animation-delay:0s",   // "a"
+                "animation-delay:0.3s", // arrowhead
+                "animation-delay:0.3s", // arrow
+                "animation-delay:0.6s", // circle
+            ],
+            "{}",
+            svg
+        );
+
+        let plain = crate::pikchr("box").expect("render failed");
+        assert!(!plain.contains("animation"), "{}", plain);
+    }
+    #[test]
+    fn render_embed_font() {
+        let options = RenderOptions {
+            embed_font: Some(b"wOFF\0\x01\0\0fake".to_vec()),
+            ..Default::default()
+        };
+        let svg =
+            crate::pikchr_with_options("box \"a\"\n\"b\" mono", &options).expect("render failed");
+        assert!(svg.contains("@font-face"), "{}", svg);
+        assert!(
+            svg.contains(r#"url(data:font/woff;base64,d09GRgABAABmYWtl) format("woff")"#),
+            "{}",
+            svg
+        );
+        assert!(svg.contains("text:not([font-family])"), "{}", svg);
+
+        // The format comes from the magic bytes; unknown data gets no format() hint
+        for (font, src) in [
+            (
+                &b"wOF2fake"[..],
+                r#"url(data:font/woff2;base64,d09GMmZha2U=) format("woff2")"#,
+            ),
+            (
+                b"\0\x01\0\0ttf",
+                r#"url(data:font/ttf;base64,AAEAAHR0Zg==) format("truetype")"#,
+            ),
+            (
+                b"OTTOotf",
+                r#"url(data:font/otf;base64,T1RUT290Zg==) format("opentype")"#,
+            ),
+            (b"??", "url(data:application/octet-stream;base64,Pz8=);"),
+        ] {
+            let options = RenderOptions {
+                embed_font: Some(font.to_vec()),
+                ..Default::default()
+            };
+            let svg = crate::pikchr_with_options("box \"a\"", &options).expect("render failed");
+            assert!(svg.contains(src), "{}", svg);
+        }
+
+        let plain = crate::pikchr("box \"a\"").expect("render failed");
+        assert!(!plain.contains("@font-face"), "{}", plain);
+    }
+
+    #[test]
+    fn render_print_output() {
+        // Like C, print output comes first, followed by the diagram
+        let out = crate::pikchr("print 1+1\nbox").expect("render failed");
+        assert!(out.starts_with("2<br>\n"), "{}", out);
+        assert!(out.contains("<svg"), "{}", out);
+
+        // With print_to_stderr the lines leave the output
+        let options = RenderOptions {
+            print_to_stderr: true,
+            ..Default::default()
+        };
+        let out = crate::pikchr_with_options("print 1+1\nbox", &options).expect("render failed");
+        assert!(out.starts_with("<svg"), "{}", out);
+        assert!(!out.contains("<br>"), "{}", out);
+
+        // With an on_print hook the lines go to the caller instead
+        let hooked = |input: &str| {
+            use crate::render::{RenderContext, render_with_context};
+            use std::cell::RefCell;
+            use std::rc::Rc;
+
+            let program = crate::parse::parse(input).expect("parse failed");
+            let lines = Rc::new(RefCell::new(Vec::new()));
+            let mut ctx = RenderContext::new();
+            let sink = Rc::clone(&lines);
+            ctx.on_print = Some(Box::new(move |line| {
+                sink.borrow_mut().push(line.to_string())
+            }));
+            let out = render_with_context(&program, &RenderOptions::default(), ctx)
+                .expect("render failed");
+            (out, lines.take())
+        };
+        let (out, lines) = hooked("print 1+1\nbox");
+        assert!(out.contains("<svg"), "{}", out);
+        assert!(!out.contains("<br>"), "{}", out);
+        assert_eq!(lines, ["2"]);
+        let (out, _) = hooked("print 1+1");
+        assert_eq!(out, "<!-- empty pikchr diagram -->\n");
+    }
+
+    #[test]
+    fn render_assert() {
+        // Failed assertions report both sides with the same messages as C
+        for (bad, message) in [
+            ("assert(2 == 3)", "assertion failed: 2 != 3"),
+            (
+                "A: box\nassert(A.n == A.s)",
+                "assertion failed: (0,0.25) != (0,-0.25)",
+            ),
+            ("[ assert(1 == 2) ]", "assertion failed: 1 != 2"),
+        ] {
+            let err = crate::pikchr(bad).expect_err(bad);
+            assert!(err.contains(message), "{}: {}", bad, err);
+        }
+    }
+
+    #[test]
+    fn render_assert_object_properties() {
+        // Property access compares computed sizes
+        let err = crate::pikchr("A: box\nB: box wid 1\nassert(A.wid == B.wid)")
+            .expect_err("widths differ");
+        assert!(err.contains("assertion failed: 0.75 != 1"), "{}", err);
+    }
+
+    #[test]
+    fn render_assert_compares_positions() {
+        // Object references compare as their centers, not by identity, like C
+        let err = crate::pikchr("box\ncircle\nassert(previous == first box)")
+            .expect_err("different centers");
+        assert!(err.contains("(0.625,0) != (0,0)"), "{}", err);
+
+        // The grammar gap files are not in the comparison harness; their asserts hold
+        for file in [
+            "tests/grammar-gaps/gap05-diameter.pikchr",
+            "tests/grammar-gaps/gap07-of-the-way-between.pikchr",
+        ] {
+            let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(file);
+            let source = std::fs::read_to_string(&path).unwrap();
+            assert!(source.contains("assert("), "{}", file);
+            if let Err(e) = crate::pikchr(&source) {
+                panic!("{}: {}", file, e);
+            }
+        }
+    }
+
+    #[test]
+    fn render_edge_point_then_segments() {
+        // C needs `go` before a leading edge point or heading; pikru also accepts the
+        // bare forms, and draws them the same
+        for (bare, with_go) in [
+            ("line ne then right", "line go ne then right"),
+            ("line 0.5 ne", "line go 0.5 ne"),
+            ("line 1 heading 45", "line go 1 heading 45"),
+        ] {
+            assert_eq!(
+                crate::pikchr(bare).expect(bare),
+                crate::pikchr(with_go).expect(with_go),
+                "{:?}",
+                bare
+            );
+        }
+    }
+
+    #[cfg(feature = "svgz")]
+    #[test]
+    fn pikchr_to_svgz_round_trips() {
+        use flate2::read::GzDecoder;
+        use std::io::Read;
+
+        let source = "box \"a\"\narrow\ncircle";
+        let svgz = crate::pikchr_to_svgz(source).expect("render failed");
+        let mut svg = String::new();
+        GzDecoder::new(&svgz[..])
+            .read_to_string(&mut svg)
+            .expect("not gzip");
+        assert!(svg.contains("<svg"), "{}", svg);
+        assert_eq!(svg, crate::pikchr(source).unwrap());
+        assert!(crate::pikchr_to_svgz("box at Nope").is_err());
+    }
+
+    #[test]
+    fn render_pretty() {
+        let source = "box \"a\"\narrow\ncircle";
+        let compact = crate::pikchr(source).expect("render failed");
+        let options = RenderOptions {
+            pretty: true,
+            ..Default::default()
+        };
+        let pretty = crate::pikchr_with_options(source, &options).expect("render failed");
+
+        // svg, path, text, polygon, path, circle: one line each at least
+        assert!(pretty.lines().count() >= 6, "{}", pretty);
+        assert!(pretty.contains("\n  <path"), "{}", pretty);
+        for fragment in [
+            "d=\"M2.16,74.16L110.16,74.16L110.16,2.16L2.16,2.16Z\"",
+            "viewBox=\"0 0 256.32 76.32\"",
+        ] {
+            assert!(compact.contains(fragment), "{}", compact);
+            assert!(pretty.contains(fragment), "{}", pretty);
+        }
+    }
+
+    #[test]
+    fn render_css_color_functions() {
+        let named = crate::pikchr("box fill Red color Blue").expect("render failed");
+        assert!(
+            has_style(
+                &named,
+                "fill:rgb(255,0,0);stroke-width:2.16;stroke:rgb(0,0,255);"
+            ),
+            "{}",
+            named
+        );
+        for source in [
+            "box fill rgb(255,0,0) color rgb(0, 0, 255)",
+            "box fill hsl(0,100%,50%) color hsl(240,100%,50%)",
+            "fill = hsl(360,100%,50%)\ncolor = rgb(0,0,255)\nbox",
+            "box fill hsl(-360, 100%, 50%) color hsl(-120,100%,50%)",
+        ] {
+            let svg = crate::pikchr(source).expect("render failed");
+            assert_eq!(svg, named, "{}", source);
+        }
+        // hsl saturation and lightness need a %, and every function takes three numbers
+        for bad in [
+            "box fill hsl(1,2,3)",
+            "box fill rgb(,,)",
+            "box fill rgb(1,2)",
+        ] {
+            assert!(crate::pikchr(bad).is_err(), "{} should not parse", bad);
+        }
+        let err = crate::pikchr("box fill rgb(300,0,0)").expect_err("out of range");
+        assert!(
+            err.contains("<input>:1:10"),
+            "error should point at the color: {}",
+            err
+        );
+    }
+
+    #[test]
+    fn render_pikchr_date() {
+        let compact = crate::pikchr("box").expect("render failed");
+        assert!(!compact.contains("data-pikchr-date"), "{}", compact);
+
+        let options = RenderOptions {
+            pikchr_date: true,
+            ..Default::default()
+        };
+        let svg = crate::pikchr_with_options("box", &options).expect("render failed");
+        let root = &svg[svg.find("<svg").unwrap()..];
+        let root = &root[..root.find('>').unwrap()];
+        // Same value the tracked C check-in emits, so output stays deterministic
+        assert!(
+            root.ends_with(" data-pikchr-date=\"20250512153813\""),
+            "{}",
+            svg
+        );
+        assert_eq!(svg.matches("data-pikchr-date").count(), 1, "{}", svg);
+    }
+
+    #[test]
+    fn render_compat_level() {
+        use crate::CompatLevel;
+
+        // Pikchr 1.0 predates data-pikchr-date
+        let options = RenderOptions {
+            pikchr_date: true,
+            compat: CompatLevel::Pikchr1_0,
+            ..Default::default()
+        };
+        let svg = crate::pikchr_with_options("box", &options).expect("render failed");
+        assert!(!svg.contains("data-pikchr-date"), "{}", svg);
+        assert_eq!(svg, crate::pikchr("box").unwrap());
+
+        assert_eq!(RenderOptions::default().compat, CompatLevel::Pikchr1_1);
+        let options = RenderOptions {
+            pikchr_date: true,
+            compat: CompatLevel::Pikchr1_1,
+            ..Default::default()
+        };
+        let svg = crate::pikchr_with_options("box", &options).expect("render failed");
+        assert!(svg.contains("data-pikchr-date"), "{}", svg);
+    }
+
+    #[test]
+    fn render_group_arrows() {
+        let options = RenderOptions {
+            group_arrows: true,
+            ..Default::default()
+        };
+        let svg = crate::pikchr_with_options("box\narrow \"go\"\nline <->\nline", &options)
+            .expect("render failed");
+        let groups: Vec<&str> = svg
+            .split("<g class=\"arrow\">")
+            .skip(1)
+            .map(|rest| &rest[..rest.find("</g>").unwrap()])
+            .collect();
+        assert_eq!(groups.len(), 2, "{}", svg);
+        // The arrow keeps its label; the double-headed line has both heads
+        assert_eq!(groups[0].matches("<path").count(), 1, "{}", svg);
+        assert_eq!(groups[0].matches("<polygon").count(), 1, "{}", svg);
+        assert!(groups[0].contains(">go</text>"), "{}", svg);
+        assert_eq!(groups[1].matches("<polygon").count(), 2, "{}", svg);
+        // The box and the headless line stay ungrouped
+        assert_eq!(svg.matches("<path").count(), 4, "{}", svg);
+        assert_eq!(svg.matches("<g").count(), 2, "{}", svg);
+
+        let plain = crate::pikchr("box\narrow \"go\"\nline <->\nline").unwrap();
+        assert!(!plain.contains("<g"), "{}", plain);
+
+        // Reveal delays still reach the grouped elements
+        let options = RenderOptions {
+            group_arrows: true,
+            animate_reveal: true,
+            ..Default::default()
+        };
+        let svg = crate::pikchr_with_options("arrow", &options).unwrap();
+        assert_eq!(svg.matches("animation-delay:").count(), 2, "{}", svg);
+    }
+
+    #[test]
+    fn render_includes_from_path() {
+        let dir = std::env::temp_dir().join(format!("pikru-include-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("parts")).unwrap();
+        let write = |name: &str, source: &str| std::fs::write(dir.join(name), source).unwrap();
+        write(
+            "main.pikchr",
+            "include \"parts/a.pikchr\"\nB: box\nthing\nassert(B.w == A.e)",
+        );
+        // Nested includes are relative to the including file, and macros carry over
+        write("parts/a.pikchr", "A: box\ninclude \"defs.pikchr\"");
+        write("parts/defs.pikchr", "define thing { circle }");
+        write("loop.pikchr", "box\ninclude \"parts/loop.pikchr\"");
+        write("parts/loop.pikchr", "include \"../loop.pikchr\"");
+        write("missing.pikchr", "include \"nope.pikchr\"");
+
+        let options = RenderOptions {
+            resolve_includes: true,
+            ..Default::default()
+        };
+        let svg =
+            crate::pikchr_from_path(dir.join("main.pikchr"), &options).expect("render failed");
+        assert_eq!(svg.matches("<path").count(), 2, "{}", svg);
+        assert_eq!(svg.matches("<circle").count(), 1, "{}", svg);
+
+        let err = crate::pikchr_from_path(dir.join("loop.pikchr"), &options)
+            .expect_err("cycle must fail");
+        assert!(err.contains("include cycle"), "{}", err);
+        let err = crate::pikchr_from_path(dir.join("missing.pikchr"), &options)
+            .expect_err("missing file must fail");
+        assert!(err.contains("cannot include"), "{}", err);
+
+        // Includes are opt-in, and need a file to be relative to
+        let err = crate::pikchr_from_path(dir.join("main.pikchr"), &RenderOptions::default())
+            .expect_err("includes are off by default");
+        assert!(err.contains("pikru extension"), "{}", err);
+        let err = crate::pikchr_with_options("include \"main.pikchr\"", &options)
+            .expect_err("no path to resolve against");
+        assert!(err.contains("pikru extension"), "{}", err);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn render_initial_direction() {
+        let options = RenderOptions {
+            initial_direction: crate::ast::Direction::Down,
+            ..Default::default()
+        };
+        let svg =
+            crate::pikchr_with_options("box\nbox\nright\narrow", &options).expect("render failed");
+        // Same as starting the source with `down`: the boxes stack vertically
+        assert_eq!(svg, crate::pikchr("down\nbox\nbox\nright\narrow").unwrap());
+        assert!(svg.contains("viewBox=\"0 0 188.64 148.32\""), "{}", svg);
+        assert_ne!(svg, crate::pikchr("box\nbox\nright\narrow").unwrap());
+    }
+
+    #[test]
+    fn render_background() {
+        let plain = crate::pikchr("box").unwrap();
+        let options = RenderOptions {
+            background: Some(crate::types::Color::Rgb(32, 32, 32)),
+            ..Default::default()
+        };
+        let svg = crate::pikchr_with_options("box", &options).expect("render failed");
+        // The viewBox is unchanged and the background is painted before the box
+        assert!(svg.contains(r#"viewBox="0 0 112.32 76.32""#), "{}", svg);
+        let body = &svg[svg.find('>').unwrap() + 1..];
+        assert!(
+            body.starts_with(
+                r#"<path d="M0,0L112.32,0L112.32,76.32L0,76.32Z" style="fill:rgb(32,32,32);stroke:none;""#
+            ),
+            "{}",
+            svg
+        );
+        assert_eq!(svg.matches("<path").count(), 2, "{}", svg);
+        assert_eq!(
+            svg.matches("<path").count(),
+            plain.matches("<path").count() + 1
+        );
+
+        // Without translate_origin it follows the viewBox into diagram space
+        let options = RenderOptions {
+            background: Some("white".parse().unwrap()),
+            translate_origin: false,
+            ..Default::default()
+        };
+        let svg = crate::pikchr_with_options("box", &options).expect("render failed");
+        assert!(
+            svg.contains(
+                r#"d="M-56.16,-38.16L56.16,-38.16L56.16,38.16L-56.16,38.16Z" style="fill:rgb(255,255,255);"#
+            ),
+            "{}",
+            svg
+        );
+    }
+
+    #[test]
+    fn render_emit_layers() {
+        let options = RenderOptions {
+            emit_layers: true,
+            ..Default::default()
+        };
+        // `behind` puts the second box one layer under A, so it is drawn first
+        let svg = crate::pikchr_with_options("A: box\nbox behind A\ncircle", &options)
+            .expect("render failed");
+        assert_eq!(svg.matches("data-layer=\"999\"").count(), 1, "{}", svg);
+        assert_eq!(svg.matches("data-layer=\"1000\"").count(), 2, "{}", svg);
+        assert!(
+            svg.find("data-layer=\"999\"") < svg.find("data-layer=\"1000\""),
+            "{}",
+            svg
+        );
+
+        let plain = crate::pikchr("A: box\nbox behind A").expect("render failed");
+        assert!(!plain.contains("data-layer"), "{}", plain);
+    }
+
+    #[test]
+    fn render_without_translate_origin() {
+        // Without translation every coordinate moves by exactly the viewBox origin,
+        // so both renders show the same picture
+        let options = RenderOptions {
+            translate_origin: false,
+            ..Default::default()
+        };
+        let cases = [
+            (
+                "box",
+                "0 0 112.32 76.32",
+                "M2.16,74.16L110.16,74.16L110.16,2.16L2.16,2.16Z",
+                "-56.16 -38.16 112.32 76.32",
+                "M-54,36L54,36L54,-36L-54,-36Z",
+            ),
+            (
+                "line right 1in from (1,2)",
+                "0 0 148.32 4.32",
+                "M2.16,2.16L146.16,2.16",
+                "141.84 -290.16 148.32 4.32",
+                "M144,-288L288,-288",
+            ),
+        ];
+        for (input, viewbox, path, moved_viewbox, moved_path) in cases {
+            let svg = crate::pikchr(input).expect("render failed");
+            assert!(svg.contains(&format!("viewBox=\"{}\"", viewbox)), "{}", svg);
+            assert!(svg.contains(&format!("d=\"{}\"", path)), "{}", svg);
+
+            let svg = crate::pikchr_with_options(input, &options).expect("render failed");
+            assert!(
+                svg.contains(&format!("viewBox=\"{}\"", moved_viewbox)),
+                "{}",
+                svg
+            );
+            assert!(svg.contains(&format!("d=\"{}\"", moved_path)), "{}", svg);
+        }
+    }
+
+    #[test]
+    fn render_on_object_placed_callback() {
+        use crate::ast::ClassName;
+        use crate::render::{RenderContext, RenderOptions, render_with_context};
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        // The sublist counts as one object; its children are not reported.
+        // Objects report the class they were written as, so an arrow is an Arrow
+        let input = "box\narrow\ncircle\nS: [ box; box ]\ntext \"done\"";
+        let program = crate::parse::parse(input).expect("parse failed");
+
+        let placed = Rc::new(RefCell::new(Vec::new()));
+        let mut ctx = RenderContext::new();
+        let sink = Rc::clone(&placed);
+        ctx.on_object_placed = Some(Box::new(move |obj| sink.borrow_mut().push(obj.class())));
+
+        render_with_context(&program, &RenderOptions::default(), ctx).expect("render failed");
+        assert_eq!(
+            *placed.borrow(),
+            vec![
+                ClassName::Box,
+                ClassName::Arrow,
+                ClassName::Circle,
+                ClassName::Sublist,
+                ClassName::Text
+            ]
+        );
+    }
+
+    #[test]
+    fn render_on_unresolved_collects_names() {
+        use crate::render::{RenderContext, RenderOptions, render_with_context};
+        use std::cell::RefCell;
+        use std::rc::Rc;
+
+        let unresolved = |input: &str| {
+            let program = crate::parse::parse(input).expect("parse failed");
+            let names = Rc::new(RefCell::new(Vec::new()));
+            let mut ctx = RenderContext::new();
+            let sink = Rc::clone(&names);
+            ctx.on_unresolved = Some(Rc::new(move |name: &str| {
+                sink.borrow_mut().push(name.to_string())
+            }));
+            let result = render_with_context(&program, &RenderOptions::default(), ctx);
+            assert_eq!(result.is_err(), !names.borrow().is_empty(), "{}", input);
+            names.take()
+        };
+
+        assert_eq!(unresolved("box\nline from A to B"), ["A"]);
+        assert_eq!(
+            unresolved("A: box\narrow from A.e to 2nd circle.w"),
+            ["2nd circle"]
+        );
+        assert_eq!(unresolved("S: [ box; circle at Q ]"), ["Q"]);
+        assert_eq!(unresolved("A: [ B: box ]\nline from A.C.s"), ["A.C"]);
+        assert_eq!(unresolved("$x = $y + 1"), ["$y"]);
+        assert!(unresolved("A: box\narrow from A.e").is_empty());
+    }
+
+    #[test]
+    fn render_statements_incrementally() {
+        use crate::render::RenderContext;
+
+        let program = crate::parse::parse("box\n$x = 2\nprint $x\narrow\nS: [ box; box ]")
+            .expect("parse failed");
+        let mut ctx = RenderContext::new();
+        let mut counts = Vec::new();
+        for stmt in &program.statements {
+            ctx.render_statement(stmt).expect("render failed");
+            counts.push(ctx.object_count());
+        }
+        // Assignments and prints place nothing; the sublist counts as one object
+        assert_eq!(counts, vec![1, 1, 1, 2, 3]);
+        assert_eq!(ctx.print_lines, vec!["2".to_string()]);
+        assert!(ctx.get_object("S").is_some());
+    }
+
+    #[test]
+    fn visit_objects_walks_into_sublists() {
+        use crate::ast::ClassName;
+        use crate::render::RenderContext;
+
+        let program = crate::parse::parse("A: box\nS: [ C: circle rad 0.25; box ] with .w at A.e")
+            .expect("parse failed");
+        let mut ctx = RenderContext::new();
+        for stmt in &program.statements {
+            ctx.render_statement(stmt).expect("render failed");
+        }
+
+        let mut seen = Vec::new();
+        ctx.visit_objects(|obj| {
+            let b = obj.bounds();
+            seen.push((
+                obj.name.clone(),
+                obj.class(),
+                obj.center().x.raw(),
+                b.min.x.raw(),
+                b.max.x.raw(),
+            ));
+        });
+        // Children come right after their sublist, at their final positions
+        assert_eq!(
+            seen,
+            vec![
+                (Some("A".to_string()), ClassName::Box, 0.0, -0.375, 0.375),
+                (Some("S".to_string()), ClassName::Sublist, 1.0, 0.375, 1.625),
+                (
+                    Some("C".to_string()),
+                    ClassName::Circle,
+                    0.625,
+                    0.375,
+                    0.875
+                ),
+                (None, ClassName::Box, 1.25, 0.875, 1.625),
+            ]
+        );
+    }
+
+    #[test]
+    fn render_non_finite_coordinates_are_errors() {
+        let cases = [
+            ("box at (1/0, 2)", "Division by zero"),
+            ("box at (2, 1/(2-2))", "Division by zero"),
+            (
+                "box at 1e308 right of (1e308, 0)",
+                "Arithmetic overflow in position",
+            ),
+            (
+                "line from (0,0) to 1e308 above (0, 1e308)",
+                "Arithmetic overflow in position",
+            ),
+        ];
+        for (input, message) in cases {
+            let err = crate::pikchr(input).expect_err(input);
+            assert!(
+                err.contains(message),
+                "{:?}: expected {:?}, got {}",
+                input,
+                message,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn render_fill_rejects_unknown_color() {
+        // A typo'd color name must not silently become "no fill"
+        let result = crate::pikchr("box\nfill = Bisqe\nbox");
+        let err = result.expect_err("unknown fill color should be an error");
+        assert!(
+            err.contains("Bisqe"),
+            "error should name the color: {}",
+            err
+        );
+        assert!(
+            err.contains("<input>:2:1"),
+            "error should point at the assignment: {}",
+            err
+        );
+
+        let svg = crate::pikchr("fill = Bisque\nbox").expect("render failed");
+        assert!(!svg.contains("fill:none"), "box should be filled: {}", svg);
+
+        crate::pikchr("fill = None\nbox").expect("fill = None should be accepted");
+        crate::pikchr("fill = #ffe4c4\nbox").expect("hex fill should be accepted");
+    }
+
+    #[test]
+    fn render_compound_assign_rejects_colors() {
+        // C treats colors as plain numbers; pikru refuses to do arithmetic on them
+        for input in [
+            "$x = red\n$x += 1\nbox",
+            "$x = red\n$x *= 2\nbox",
+            "$x = 5\n$x += red\nbox",
+            "$x = red\n$x -= blue\nbox",
+            "[ $x = red\n$x /= 2 ]",
+        ] {
+            let err = crate::pikchr(input).expect_err(input);
+            assert!(err.contains("color"), "{:?}: {}", input, err);
+        }
+
+        // A scalar combined with a length no longer drops the old value
+        // (viewBox taken from the C pikchr binary)
+        let svg = crate::pikchr("$x = 2\n$x += 1cm\nbox wid $x ht 1").expect("render failed");
+        assert!(svg.contains("viewBox=\"0 0 349.013 148.32\""), "{}", svg);
+    }
+
+    #[test]
+    fn render_reset_restores_builtin_variables() {
+        // The first box keeps its 2in width, the one after reset is back to 0.75in
+        let svg = crate::pikchr("boxwid = 2\nbox\nreset\nbox").expect("render failed");
+        assert!(svg.contains("viewBox=\"0 0 400.32 76.32\""), "{}", svg);
+
+        // fill overrides are dropped, user variables survive
+        let svg = crate::pikchr("fill = red\n$w = 1\nreset\nbox wid $w").expect("render failed");
+        assert!(svg.contains("viewBox=\"0 0 148.32 76.32\""), "{}", svg);
+        assert!(svg.contains("fill:none"), "{}", svg);
+
+        // `reset` is still usable as a variable name
+        let svg = crate::pikchr("reset = 1\nbox wid reset").expect("render failed");
+        assert!(svg.contains("viewBox=\"0 0 148.32 76.32\""), "{}", svg);
+    }
+
+    #[test]
+    fn render_math_extension_functions() {
+        // atan2 returns degrees like cos()/sin() take them; exp/log are natural
+        let input = "$a = atan2(1, 1)\nbox wid $a / 45 ht log(exp(1)) * exp(0)";
+        let svg = crate::pikchr(input).expect("render failed");
+        assert!(svg.contains("viewBox=\"0 0 148.32 148.32\""), "{}", svg);
+
+        // Names stay usable as plain variables
+        crate::pikchr("log = 1\nbox wid log").expect("log should still be a variable");
+
+        assert!(crate::pikchr("box wid log(0)").is_err());
+    }
+
+    #[test]
+    fn render_position_coords() {
+        // Each extension form must place the circle like its C-compatible spelling
+        let cases = [
+            ("(A.ne).x, (1st box).y", "A.ne.x, A.y"),
+            (
+                "(A.ne + (1, 0.5)).x, (A.ne + (1, 0.5)).y",
+                "A.ne.x + 1, A.ne.y + 0.5",
+            ),
+            (
+                "(0.25 between A.w and A.e).x, (A.s).y",
+                "A.w.x + A.wid / 4, A.s.y",
+            ),
+        ];
+        for (extended, plain) in cases {
+            let render = |at: &str| {
+                crate::pikchr(&format!("A: box\ncircle rad 0.1 at {}", at)).expect("render failed")
+            };
+            assert_eq!(render(extended), render(plain), "{}", extended);
+        }
+    }
+
+    #[test]
+    fn render_npoints_property() {
+        // The line is invisible so only the box sized from its vertex count sets the viewBox
+        let cases = [
+            (
+                "L: line right 1 then down 1 then right 1 invis\nbox wid L.npoints ht 1",
+                "0 0 580.32 148.32",
+            ),
+            // Non line-like objects have no vertices
+            (
+                "B: box invis\nbox wid B.npoints + 1 ht 1",
+                "0 0 148.32 148.32",
+            ),
+        ];
+        for (input, viewbox) in cases {
+            let svg = crate::pikchr(input).expect("render failed");
+            assert!(
+                svg.contains(&format!("viewBox=\"{}\"", viewbox)),
+                "{:?}: expected viewBox {}, got {}",
+                input,
+                viewbox,
+                svg
+            );
+        }
+    }
+
+    #[test]
+    fn render_same_as_other_class() {
+        // C would draw this circle from the box's zero rad; pikru keeps it round with
+        // the box width as diameter, the same as `circle rad 0.5`
+        let svg = crate::pikchr("B: box wid 1 ht 0.5\ncircle same as B").unwrap();
+        assert_eq!(
+            svg,
+            crate::pikchr("B: box wid 1 ht 0.5\ncircle rad 0.5").unwrap()
+        );
+    }
+
+    #[test]
+    fn svg_reference_check_catches_dangling_ids() {
+        use crate::render::svg::find_dangling_reference;
+
+        assert_eq!(
+            find_dangling_reference(r##"<svg><path style="fill:url(#grad)"/></svg>"##),
+            Some("grad".to_string())
+        );
+        assert_eq!(
+            find_dangling_reference(
+                r##"<svg><clipPath id="clip"/><g clip-path="url(#clip)"><use href="#gone"/></g></svg>"##
+            ),
+            Some("gone".to_string())
+        );
+        assert_eq!(
+            find_dangling_reference(
+                r##"<svg><linearGradient id="grad"/><path style="fill:url(#grad)"/></svg>"##
+            ),
+            None
+        );
+
+        // Label text that looks like a reference is not markup
+        let svg = crate::pikchr(r#"box "url(#nowhere)" fill red"#).expect("render failed");
+        assert_eq!(find_dangling_reference(&svg), None);
+    }
+
+    #[test]
+    fn render_sublist_size_is_fixed() {
+        // Like C, a sublist is sized by its children; width/height/radius are rejected
+        // rather than silently ignored or used to scale the group
+        for input in [
+            "[box; box] wid 3",
+            "[box; box] wid 3 ht 1",
+            "[box; box] height 200%",
+            "[box; box] diameter 1",
+        ] {
+            let err = crate::pikchr(input).expect_err(input);
+            assert!(
+                err.contains("value already fixed by prior constraints"),
+                "{:?}: {}",
+                input,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn render_sublist_references_are_scoped() {
+        // Like C, a sublist has its own object list and inherits no objects: ordinal,
+        // `previous` and name references to the enclosing diagram fail instead of
+        // silently using the cursor position
+        for input in [
+            "box; [ circle at previous.e ]",
+            "box; [ line from last box.e right ]",
+            "box; [ box; box with .w at 2nd box.e ]",
+            "box; [ arrow from 1st box.e ]",
+            "B: box; [ circle at B.e ]",
+            "box \"X\"; [ circle at X.e ]",
+            "line from previous.e right",
+            "circle at previous",
+        ] {
+            let err = crate::pikchr(input).expect_err(input);
+            assert!(err.contains("no such object"), "{:?}: {}", input, err);
+        }
+    }
+
+    #[test]
+    fn render_macro_arguments() {
+        // Like C, a macro takes at most nine arguments
+        let err = crate::pikchr("define mm { box }\nmm(1,2,3,4,5,6,7,8,9,10)")
+            .expect_err("ten arguments");
+        assert!(err.contains("too many macro arguments - max 9"), "{}", err);
+    }
+
+    #[test]
+    fn render_count_objects() {
+        use crate::render::RenderContext;
+
+        // A macro can branch on how many boxes exist: the first box is narrow, the
+        // rest are wide. Same output as spelling the widths out (checked against C).
+        let svg = crate::pikchr(
+            "define next { box wid (1 + min(count(box), 1)) * 0.5 }\nnext\nnext\nnext",
+        )
+        .expect("render failed");
+        assert_eq!(
+            svg,
+            crate::pikchr("box wid 0.5\nbox wid 1\nbox wid 1").expect("render failed")
+        );
+        assert!(svg.contains("viewBox=\"0 0 364.32 76.32\""), "{}", svg);
+
+        let program = crate::parse::parse(
+            "box\ncircle\ncircle\n[box]\nprint count(), count(circle), count(box), count([]), count(arc)",
+        )
+        .expect("parse failed");
+        let mut ctx = RenderContext::new();
+        for stmt in &program.statements {
+            ctx.render_statement(stmt).expect("render failed");
+        }
+        assert_eq!(ctx.print_lines, vec!["4 2 1 1 0".to_string()]);
+
+        // Inside a sublist only the sublist's own objects are counted
+        assert_eq!(
+            crate::pikchr("box\n[box; box wid count(box)]").expect("render failed"),
+            crate::pikchr("box\n[box; box wid 1]").expect("render failed")
+        );
+
+        // `count` is not reserved
+        assert!(crate::pikchr("count = 2\nbox wid count").is_ok());
+    }
+
+    #[test]
+    fn render_unresolved_reference_names_the_object() {
+        // Errors instead of drawing at the cursor, and say which reference failed.
+        // Like C there are no forward references: `B` is unknown until placed.
+        let cases = [
+            ("box at Fooo.ne", "no such object: Fooo"),
+            ("box at B.e\nB: box", "no such object: B"),
+            ("M: [box]\ncircle at M.X", "no such object: M.X"),
+            ("box\ncircle at 2nd box", "no such object: 2nd box"),
+            (
+                "box\ncircle at 2nd last box",
+                "no such object: 2nd last box",
+            ),
+            (
+                "box\ncircle at 1st circle of last box",
+                "no such object: 1st circle of last box",
+            ),
+            ("box wid Nope.wid", "no such object: Nope"),
+            ("box wid Nope.x", "no such object: Nope"),
+            ("box wid Nope.n.y", "no such object: Nope"),
+            ("box wid 2nd vertex of Nope.x", "no such object: Nope"),
+        ];
+        for (input, message) in cases {
+            let err = crate::pikchr(input).expect_err(input);
+            assert!(
+                err.contains(message),
+                "{:?}: expected {:?}, got {}",
+                input,
+                message,
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn render_justified_text_position_follows_string() {
+        // Like C, text positions must follow the string
+        assert!(crate::pikchr("A: box\ntext \"x\" at A.ne ljust").is_err());
+    }
+
+    #[test]
+    fn render_forward_references_are_errors() {
+        // C resolves strictly in source order, so a label is unknown until its
+        // object is placed, whichever attribute refers to it.
+        let cases = [
+            (
+                "box with .n at Later.s\nLater: box",
+                "no such object: Later",
+            ),
+            ("arrow from A.e to B.w\nA: box\nB: box", "no such object: A"),
+            ("A: box\narrow from A.e to B.w\nB: box", "no such object: B"),
+            (
+                "line right then to Later.n\nLater: box",
+                "no such object: Later",
+            ),
+            ("box behind Later\nLater: box", "no such object: Later"),
+            ("box same as Later\nLater: box", "no such object: Later"),
+        ];
+        for (input, message) in cases {
+            let err = crate::pikchr(input).expect_err(input);
+            assert!(
+                err.contains(message),
+                "{:?}: expected {:?}, got {}",
+                input,
+                message,
+                err
+            );
+        }
+
+        // Once placed, the same references resolve
+        assert!(crate::pikchr("Later: box\nbox with .n at Later.s").is_ok());
+    }
+
+    #[test]
+    fn render_heading_with_undefined_angle() {
+        // An undefined angle variable is an error, like in C
+        assert!(crate::pikchr("line go 1 heading $nope").is_err());
+    }
+
+    #[test]
+    fn render_at_nth_vertex() {
+        // Like C, out-of-range ordinals and non-line objects are errors
+        let err = crate::pikchr("L: line right 1 then up 1 then right 1\nbox at 5th vertex of L")
+            .expect_err("5th vertex of a 4-vertex line");
+        assert!(err.contains("no such vertex"), "{}", err);
+        let err = crate::pikchr("B: box\ncircle at 2nd vertex of B").expect_err("vertex of a box");
+        assert!(err.contains("not a line"), "{}", err);
+    }
+
+    #[test]
+    fn render_rejects_string_formatting() {
+        // C has no `"fmt" % expr`; report it instead of a bare syntax error
+        for input in [
+            "box \"%g\" % 5",
+            "\"w=%g\" % boxwid",
+            "box \"a\" \"%g\" % 5",
+        ] {
+            let err = crate::pikchr(input).expect_err(input);
+            assert!(
+                err.contains("string formatting is not supported"),
+                "{:?}: {}",
+                input,
+                err
+            );
+        }
+
+        // A literal %g in a label is still plain text, like in C
+        let svg = crate::pikchr("box \"x=%g\"").expect("render failed");
+        assert!(svg.contains(">x=%g</text>"), "{}", svg);
+    }
+    #[test]
+    fn render_html_entities_as_characters() {
+        // C copies &alpha; through, which XML does not define. Named HTML entities
+        // become characters, XML's own and numeric references stay, unknown names
+        // are escaped. Text is still measured like C, so the viewBox is C's.
+        let svg = crate::pikchr("box \"&alpha; &amp; &lt; &#945; &foo; a&b\"").unwrap();
+        assert!(svg.contains("viewBox=\"0 0 154.426 76.32\""), "{}", svg);
+        assert!(!svg.contains("&amp;alpha;"), "{}", svg);
+        let expected = "α &amp; &lt; &#945; &amp;foo; a&amp;b".replace(' ', "\u{a0}");
+        assert!(svg.contains(&format!(">{}</text>", expected)), "{}", svg);
+
+        let svg = crate::pikchr("text \"x&sup2; &rarr; &rightarrow;\"").unwrap();
+        assert!(svg.contains(">x²\u{a0}→\u{a0}→</text>"), "{}", svg);
+    }
+
+    #[test]
+    fn render_label_above_distance() {
+        // `above 0.2` is a pikru extension. The extra 0.2in (28.8px) grows the viewBox
+        // upward, so the label keeps its y while the arrow moves down.
+        let svg = crate::pikchr("arrow \"x\" above 0.2").expect("render failed");
+        assert!(svg.contains("viewBox=\"0 0 80.64 59.22\""), "{}", svg);
+        assert!(svg.contains("d=\"M2.16,52.74"), "{}", svg);
+        assert!(svg.contains("y=\"12.24\""), "{}", svg);
+
+        // The distance is any expression, evaluated when the label is placed
+        for source in [
+            "arrow \"x\" above 0.508cm",
+            "d = 0.1\narrow \"x\" above (d*2)",
+            "d = 0.2\narrow \"x\" above d",
+        ] {
+            let same = crate::pikchr(source).expect("render failed");
+            assert_eq!(same, svg, "{}", source);
+        }
+        let err = crate::pikchr("arrow \"x\" above nope").expect_err("undefined");
+        assert!(err.contains("nope"), "{}", err);
+
+        // Attributes that the keyword rule lets through as variables end the distance
+        let thick = crate::pikchr("arrow \"x\" below thickness 0.1").expect("render failed");
+        assert!(thick.contains("stroke-width:14.4;"), "{}", thick);
+    }
+
     #[test]
     fn render_all_pikchr_files() {
         // Files that are intentionally testing error handling
@@ -8,6 +8,8 @@ use crate::parse;
 use std::collections::HashMap;
 
 const MAX_EXPANSION_DEPTH: usize = 10;
+/// C macros take positional parameters `$1` to `$9`
+const MAX_MACRO_ARGS: usize = 9;
 
 /// Macro definition
 #[derive(Debug, Clone)]
@@ -87,14 +89,14 @@ fn expand_macro_call(
         }
     };
 
-    // Substitute arguments into the body
-    let mut expanded_body = macro_def.body.clone();
-
-    for (i, arg) in call.args.iter().enumerate() {
-        let placeholder = format!("${}", i + 1);
-        let replacement = macro_arg_to_string(arg);
-        expanded_body = expanded_body.replace(&placeholder, &replacement);
+    // cref: pik_parse_macro_args (pikchr.y:5196)
+    if call.args.len() > MAX_MACRO_ARGS {
+        return Err(PikruError::Generic(format!(
+            "too many macro arguments - max {}",
+            MAX_MACRO_ARGS
+        )));
     }
+    let expanded_body = substitute_params(&macro_def.body, &call.args);
 
     // Parse the expanded body
     let expanded_program = parse::parse(&expanded_body)?;
@@ -107,57 +109,38 @@ fn expand_macro_call(
     Ok(())
 }
 
-/// Convert a macro argument to its string representation
-fn macro_arg_to_string(arg: &MacroArg) -> String {
-    match arg {
-        MacroArg::String(s) => format!("\"{}\"", s),
-        MacroArg::Expr(e) => expr_to_string(e),
-        MacroArg::Ident(s) => s.clone(),
-    }
-}
-
-/// Convert an expression to its string representation (for macro expansion)
-fn expr_to_string(expr: &Expr) -> String {
-    match expr {
-        Expr::Number(n) => {
-            if n.fract() == 0.0 {
-                format!("{}", *n as i64)
-            } else {
-                format!("{}", n)
+/// Replace `$1`..`$9` in a macro body with the call's arguments. Parameters with no
+/// argument become empty, and `$` followed by two digits or inside a string is left
+/// alone, as in C.
+// cref: pik_token_length (pikchr.y:5128), pik_tokenize (pikchr.y:5263)
+fn substitute_params(body: &str, args: &[String]) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut chars = body.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
             }
+            continue;
         }
-        Expr::Variable(v) => v.clone(),
-        Expr::PlaceName(p) => p.clone(),
-        Expr::ParenExpr(e) => format!("({})", expr_to_string(e)),
-        Expr::BinaryOp(l, op, r) => {
-            let op_str = match op {
-                BinaryOp::Add => "+",
-                BinaryOp::Sub => "-",
-                BinaryOp::Mul => "*",
-                BinaryOp::Div => "/",
-            };
-            format!("{}{}{}", expr_to_string(l), op_str, expr_to_string(r))
-        }
-        Expr::UnaryOp(op, e) => {
-            let op_str = match op {
-                UnaryOp::Neg => "-",
-                UnaryOp::Pos => "+",
-            };
-            format!("{}{}", op_str, expr_to_string(e))
-        }
-        Expr::FuncCall(fc) => {
-            let func_name = match fc.func {
-                Function::Abs => "abs",
-                Function::Cos => "cos",
-                Function::Sin => "sin",
-                Function::Int => "int",
-                Function::Sqrt => "sqrt",
-                Function::Max => "max",
-                Function::Min => "min",
-            };
-            let args: Vec<String> = fc.args.iter().map(expr_to_string).collect();
-            format!("{}({})", func_name, args.join(", "))
+        if c == '$'
+            && let Some(&d @ '1'..='9') = chars.peek()
+        {
+            let mut rest = chars.clone();
+            rest.next();
+            if !rest.peek().is_some_and(|c| c.is_ascii_digit()) {
+                chars.next();
+                let index = d as usize - '1' as usize;
+                out.push_str(args.get(index).map_or("", String::as_str));
+                continue;
+            }
         }
-        _ => String::new(),
+        in_string = c == '"';
+        out.push(c);
     }
+    out
 }
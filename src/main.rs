@@ -0,0 +1,181 @@
+//! Command-line front end, a drop-in for the C `pikchr` shell.
+//!
+//! Like C pikchr, each input is wrapped in an HTML test page unless `--svg-only`
+//! is given. Errors go to stderr and make the exit status nonzero.
+// cref: main (pikchr.y:5496)
+
+use std::io::{Read, Write};
+use std::process::ExitCode;
+
+const HTML_HEADER: &str = "<!DOCTYPE html>
+<html lang=\"en-US\">
+<head>
+<title>PIKCHR Test</title>
+<style>
+  .hidden {
+     position: absolute !important;
+     opacity: 0 !important;
+     pointer-events: none !important;
+     display: none !important;
+  }
+</style>
+<script>
+  function toggleHidden(id){
+    for(var c of document.getElementById(id).children){
+      c.classList.toggle('hidden');
+    }
+  }
+</script>
+<meta charset=\"utf-8\">
+</head>
+<body>
+";
+
+// cref: usage (pikchr.y:5415)
+fn usage() -> ExitCode {
+    eprintln!("usage: pikru [OPTIONS] [FILE ...]");
+    eprintln!("Convert Pikchr input files into SVG.  Filename \"-\" (or none) means stdin.");
+    eprintln!("All output goes to stdout.");
+    eprintln!("Options:");
+    eprintln!("   --dont-stop      Process all files even if earlier files have errors");
+    eprintln!("   --svg-only       Emit raw SVG without the HTML wrapper");
+    eprintln!("   --version        Print the pikru version");
+    ExitCode::FAILURE
+}
+
+fn main() -> ExitCode {
+    let mut svg_only = false;
+    let mut dont_stop = false;
+    let mut files = Vec::new();
+
+    for arg in std::env::args().skip(1) {
+        // Like C, options take one or two dashes and a lone "-" is stdin
+        let Some(option) = arg.strip_prefix('-').filter(|o| !o.is_empty()) else {
+            files.push(arg);
+            continue;
+        };
+        match option.strip_prefix('-').unwrap_or(option) {
+            "svg-only" => svg_only = true,
+            "dont-stop" => dont_stop = true,
+            "version" | "v" => {
+                println!("pikru {}", env!("CARGO_PKG_VERSION"));
+                return ExitCode::SUCCESS;
+            }
+            "help" | "h" => return usage(),
+            _ => {
+                eprintln!("unknown option: \"{}\"", arg);
+                return usage();
+            }
+        }
+    }
+    if files.is_empty() {
+        files.push("-".to_string());
+    }
+
+    match render_files(&files, svg_only, dont_stop) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("cannot write output: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Render each file to stdout, reporting errors on stderr. Returns whether all succeeded.
+fn render_files(files: &[String], svg_only: bool, dont_stop: bool) -> std::io::Result<bool> {
+    let mut out = std::io::stdout().lock();
+    let mut ok = true;
+    if !svg_only {
+        out.write_all(HTML_HEADER.as_bytes())?;
+    }
+    for (i, file) in files.iter().enumerate() {
+        let source = match read_input(file) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("cannot open \"{}\" for reading: {}", file, e);
+                ok = false;
+                continue;
+            }
+        };
+        let result = pikru::pikchr(&source);
+        match (&result, svg_only) {
+            (Ok(svg), true) => writeln!(out, "{}", svg)?,
+            (Ok(svg), false) => {
+                writeln!(out, "<h1>File {}</h1>", file)?;
+                write_html_entry(&mut out, i + 1, svg, &source)?;
+            }
+            (Err(e), _) => {
+                if !svg_only {
+                    writeln!(out, "<h1>File {}</h1>\n<p>ERROR</p>", file)?;
+                }
+                eprintln!("{}", e);
+                ok = false;
+                if !dont_stop {
+                    break;
+                }
+            }
+        }
+    }
+    if !svg_only {
+        writeln!(out, "</body></html>")?;
+    }
+    Ok(ok)
+}
+
+fn read_input(file: &str) -> std::io::Result<String> {
+    if file == "-" {
+        let mut source = String::new();
+        std::io::stdin().read_to_string(&mut source)?;
+        Ok(source)
+    } else {
+        std::fs::read_to_string(file)
+    }
+}
+
+/// One diagram of the test page: the SVG, with its source revealed on click.
+fn write_html_entry(
+    out: &mut impl Write,
+    id: usize,
+    svg: &str,
+    source: &str,
+) -> std::io::Result<()> {
+    writeln!(
+        out,
+        "<div id=\"svg-{id}\" onclick=\"toggleHidden('svg-{id}')\">"
+    )?;
+    writeln!(
+        out,
+        "<div style='border:3px solid lightgray;max-width:{}px;'>",
+        svg_width(svg)
+    )?;
+    writeln!(out, "{}\n</div>", svg.trim_end())?;
+    writeln!(
+        out,
+        "<pre class='hidden'>{}</pre>\n</div>",
+        escape_html(source)
+    )
+}
+
+/// Rendered width in whole pixels: the `width` attribute when the diagram is
+/// scaled, otherwise the viewBox width.
+// cref: pikchr (pikchr.y:4626) - wSVG = pik_round(rScale*w)
+fn svg_width(svg: &str) -> i64 {
+    let root = &svg[svg.find("<svg").unwrap_or(0)..];
+    let root = &root[..root.find('>').unwrap_or(root.len())];
+    let attr = |name: &str| {
+        let start = root.find(&format!(" {}=\"", name))? + name.len() + 3;
+        Some(&root[start..start + root[start..].find('"')?])
+    };
+    let width = attr("width").or_else(|| attr("viewBox")?.split(' ').nth(2));
+    width
+        .and_then(|w| w.parse::<f64>().ok())
+        .map_or(0, |w| w.round() as i64)
+}
+
+// cref: print_escape_html (pikchr.y:5429)
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
@@ -1,7 +1,8 @@
 //! Parse pest pairs into AST nodes
 
 use crate::ast::*;
-use crate::errors::PikruError;
+use crate::errors::{ParseError, PikruError};
+use crate::types::{Span, css_color_to_hex};
 use crate::{PikchrParser, Rule};
 use pest::Parser;
 use pest::iterators::Pair;
@@ -46,6 +47,8 @@ fn parse_statement(pair: Pair<Rule>) -> Result<Statement, PikruError> {
         Rule::assert_stmt => Ok(Statement::Assert(parse_assert(inner)?)),
         Rule::print_stmt => Ok(Statement::Print(parse_print(inner)?)),
         Rule::error_stmt => Ok(Statement::Error(parse_error_stmt(inner)?)),
+        Rule::reset_stmt => Ok(Statement::Reset),
+        Rule::include_stmt => Ok(Statement::Include(parse_include_stmt(inner)?)),
         Rule::object_stmt => Ok(Statement::Object(parse_object_stmt(inner)?)),
         _ => Err(PikruError::Generic(format!(
             "Unexpected rule in statement: {:?}",
@@ -66,11 +69,18 @@ fn parse_direction(pair: Pair<Rule>) -> Result<Direction, PikruError> {
 }
 
 fn parse_assignment(pair: Pair<Rule>) -> Result<Assignment, PikruError> {
+    let span = pair.as_span();
+    let span = Span::new(span.start(), span.end());
     let mut inner = pair.into_inner();
     let lvalue = parse_lvalue(inner.next().unwrap())?;
     let op = parse_assign_op(inner.next().unwrap())?;
     let rvalue = parse_rvalue(inner.next().unwrap())?;
-    Ok(Assignment { lvalue, op, rvalue })
+    Ok(Assignment {
+        lvalue,
+        op,
+        rvalue,
+        span,
+    })
 }
 
 fn parse_lvalue(pair: Pair<Rule>) -> Result<LValue, PikruError> {
@@ -144,6 +154,16 @@ fn parse_rvalue(pair: Pair<Rule>) -> Result<RValue, PikruError> {
         Rule::expr => Ok(RValue::Expr(parse_expr(inner)?)),
         Rule::PLACENAME => Ok(RValue::PlaceName(inner.as_str().to_string())),
         Rule::HEX_COLOR => Ok(RValue::PlaceName(inner.as_str().to_string())), // Pass hex color as-is
+        Rule::COLOR_FUNC => css_color_to_hex(inner.as_str())
+            .map(RValue::PlaceName)
+            .ok_or_else(|| {
+                let span = inner.as_span();
+                ParseError::InvalidColor {
+                    color: inner.as_str().to_string(),
+                    span: Span::new(span.start(), span.end()),
+                }
+                .into()
+            }),
         _ => Err(PikruError::Generic(format!(
             "Invalid rvalue: {:?}",
             inner.as_rule()
@@ -169,27 +189,12 @@ fn parse_macro_call(pair: Pair<Rule>) -> Result<MacroCall, PikruError> {
     Ok(MacroCall { name, args })
 }
 
-fn parse_macro_args(pair: Pair<Rule>) -> Result<Vec<MacroArg>, PikruError> {
-    let mut args = Vec::new();
-    for inner in pair.into_inner() {
-        if inner.as_rule() == Rule::macro_arg {
-            args.push(parse_macro_arg(inner)?);
-        }
-    }
-    Ok(args)
-}
-
-fn parse_macro_arg(pair: Pair<Rule>) -> Result<MacroArg, PikruError> {
-    let inner = pair.into_inner().next().unwrap();
-    match inner.as_rule() {
-        Rule::STRING => Ok(MacroArg::String(parse_string(inner)?)),
-        Rule::expr => Ok(MacroArg::Expr(parse_expr(inner)?)),
-        Rule::IDENT => Ok(MacroArg::Ident(inner.as_str().to_string())),
-        _ => Err(PikruError::Generic(format!(
-            "Invalid macro arg: {:?}",
-            inner.as_rule()
-        ))),
-    }
+fn parse_macro_args(pair: Pair<Rule>) -> Result<Vec<String>, PikruError> {
+    Ok(pair
+        .into_inner()
+        .filter(|inner| inner.as_rule() == Rule::macro_arg)
+        .map(|inner| inner.as_str().trim().to_string())
+        .collect())
 }
 
 fn parse_assert(pair: Pair<Rule>) -> Result<Assert, PikruError> {
@@ -253,6 +258,12 @@ fn parse_error_stmt(pair: Pair<Rule>) -> Result<ErrorStmt, PikruError> {
     Ok(ErrorStmt { message })
 }
 
+fn parse_include_stmt(pair: Pair<Rule>) -> Result<IncludeStmt, PikruError> {
+    let inner = pair.into_inner().next().unwrap();
+    let path = parse_string(inner)?;
+    Ok(IncludeStmt { path })
+}
+
 fn parse_labeled_statement(pair: Pair<Rule>) -> Result<LabeledStatement, PikruError> {
     let mut inner = pair.into_inner();
     let label = inner.next().unwrap().as_str().to_string();
@@ -294,6 +305,7 @@ fn parse_basetype(pair: Pair<Rule>) -> Result<BaseType, PikruError> {
     let first = inner.next().unwrap();
     match first.as_rule() {
         Rule::CLASSNAME => Ok(BaseType::Class(parse_classname(first)?)),
+        Rule::string_format => Err(string_format_error(&first)),
         Rule::STRING => {
             let text = parse_string(first)?;
             // Check for optional textposition (e.g., rjust, ljust, above, below)
@@ -315,6 +327,16 @@ fn parse_basetype(pair: Pair<Rule>) -> Result<BaseType, PikruError> {
     }
 }
 
+/// C pikchr has no `"%g" % expr` formatting; point at the construct instead of
+/// failing with a generic syntax error
+fn string_format_error(pair: &Pair<Rule>) -> PikruError {
+    let span = pair.as_span();
+    ParseError::UnsupportedStringFormat {
+        span: Span::new(span.start(), span.end()),
+    }
+    .into()
+}
+
 fn parse_classname(pair: Pair<Rule>) -> Result<ClassName, PikruError> {
     match pair.as_str() {
         "arc" => Ok(ClassName::Arc),
@@ -381,6 +403,7 @@ fn parse_attribute(pair: Pair<Rule>) -> Result<Attribute, PikruError> {
             let prop = parse_boolproperty(inner.next().unwrap())?;
             Ok(Attribute::BoolProperty(prop))
         }
+        Rule::string_format => Err(string_format_error(first)),
         Rule::STRING => {
             let s = parse_string(inner.next().unwrap())?;
             let textpos = inner.next().map(|p| parse_textposition(p)).transpose()?;
@@ -912,9 +935,11 @@ fn parse_textposition(pair: Pair<Rule>) -> Result<TextPosition, PikruError> {
 }
 
 fn parse_textattr(pair: Pair<Rule>) -> Result<TextAttr, PikruError> {
-    match pair.as_str() {
-        "above" => Ok(TextAttr::Above),
-        "below" => Ok(TextAttr::Below),
+    let keyword = pair.as_str().split_whitespace().next().unwrap_or("");
+    let distance = pair.into_inner().next().map(parse_expr).transpose()?;
+    match keyword {
+        "above" => Ok(TextAttr::Above(distance)),
+        "below" => Ok(TextAttr::Below(distance)),
         "center" => Ok(TextAttr::Center),
         "ljust" => Ok(TextAttr::LJust),
         "rjust" => Ok(TextAttr::RJust),
@@ -1029,12 +1054,22 @@ fn parse_primary(pair: Pair<Rule>) -> Result<Expr, PikruError> {
     let first = inner.next().unwrap();
 
     match first.as_rule() {
+        Rule::position => {
+            // Grammar: "(" ~ position ~ ")" ~ dot_xy
+            let pos = parse_position(first)?;
+            let coord_pair = inner.next().ok_or_else(|| {
+                PikruError::Generic("Missing coordinate after position".to_string())
+            })?;
+            let coord = parse_coord(coord_pair)?;
+            Ok(Expr::PositionCoord(Box::new(pos), coord))
+        }
         Rule::expr => {
             // Parenthesized expression: (expr) or (fill|color|thickness)
             Ok(Expr::ParenExpr(Box::new(parse_expr(first)?)))
         }
         Rule::func_call => parse_func_call(first),
         Rule::dist_call => parse_dist_call(first),
+        Rule::count_call => parse_count_call(first),
         Rule::NUMBER => parse_number(first),
         Rule::variable => Ok(Expr::Variable(parse_variable_name(first)?)),
         Rule::NTH => {
@@ -1085,6 +1120,7 @@ fn parse_primary(pair: Pair<Rule>) -> Result<Expr, PikruError> {
                             Rule::colorproperty => {
                                 PropertyRef::Color(parse_colorproperty(prop_pair)?)
                             }
+                            Rule::npoints => PropertyRef::NPoints,
                             _ => {
                                 return Err(PikruError::Generic(format!(
                                     "Invalid numproperty: {}",
@@ -1154,8 +1190,11 @@ fn parse_func_call(pair: Pair<Rule>) -> Result<Expr, PikruError> {
         "sin" => Function::Sin,
         "int" => Function::Int,
         "sqrt" => Function::Sqrt,
+        "exp" => Function::Exp,
+        "log" => Function::Log,
         "max" => Function::Max,
         "min" => Function::Min,
+        "atan2" => Function::Atan2,
         s => return Err(PikruError::Generic(format!("Unknown function: {}", s))),
     };
     let mut args = Vec::new();
@@ -1174,6 +1213,15 @@ fn parse_dist_call(pair: Pair<Rule>) -> Result<Expr, PikruError> {
     Ok(Expr::DistCall(Box::new(pos1), Box::new(pos2)))
 }
 
+fn parse_count_call(pair: Pair<Rule>) -> Result<Expr, PikruError> {
+    let class = match pair.into_inner().next() {
+        Some(p) if p.as_rule() == Rule::CLASSNAME => Some(NthClass::ClassName(parse_classname(p)?)),
+        Some(_) => Some(NthClass::Sublist),
+        None => None,
+    };
+    Ok(Expr::CountCall(class))
+}
+
 fn parse_number(pair: Pair<Rule>) -> Result<Expr, PikruError> {
     let raw = pair.as_str();
 
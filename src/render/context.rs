@@ -1,13 +1,24 @@
 //! Rendering context - tracks state during rendering
 
 use std::collections::HashMap;
+use std::rc::Rc;
 
-use crate::ast::Direction;
+use crate::ast::{Direction, Statement};
+use crate::errors::PikruError;
 use crate::types::{EvalValue, Length as Inches};
 
 use super::expand_object_bounds;
 use super::types::*;
 
+/// Hook called with each object as it is placed, see `RenderContext::on_object_placed`
+pub type ObjectPlacedHook = Box<dyn FnMut(&RenderedObject)>;
+
+/// Hook called with each unresolved reference, see `RenderContext::on_unresolved`
+pub type UnresolvedHook = Rc<dyn Fn(&str)>;
+
+/// Hook called with each `print` line, see `RenderContext::on_print`
+pub type PrintHook = Box<dyn FnMut(&str)>;
+
 /// Rendering context
 pub struct RenderContext {
     /// Current direction
@@ -30,9 +41,25 @@ pub struct RenderContext {
     pub current_object: Option<RenderedObject>,
     /// Macro definitions (name -> body)
     pub macros: HashMap<String, String>,
-    /// Named positions (e.g., `OUT: 6.3in right of previous.e`)
+    /// Named positions (e.g., `OUT: 6.3in right of previous.e`), in definition order
+    /// so debug labels come out deterministically. Later definitions shadow earlier ones.
     /// cref: Labeled positions are stored separately from objects
-    pub named_positions: HashMap<String, PointIn>,
+    pub named_positions: Vec<(String, PointIn)>,
+    /// Called with each object as it is placed, in placement order.
+    /// Useful for recording draw order (e.g. for SVG animation) or reporting progress.
+    /// Sublist children are placed in their own context and are not reported separately.
+    pub on_object_placed: Option<ObjectPlacedHook>,
+    /// Output of `print` statements, in order
+    pub print_lines: Vec<String>,
+    /// Called with each `print` line as its statement runs. When set, the lines go
+    /// here instead of into `print_lines` ahead of the SVG.
+    /// `RenderOptions::print_to_stderr` installs one that writes to stderr.
+    pub on_print: Option<PrintHook>,
+    /// Called with the name of each object, place or variable that a lookup failed to
+    /// find, spelled as in the source (e.g. `2nd box`, `A.B`, `$x`), just before the
+    /// lookup's error is returned. Lets tooling list unresolved references after a failed
+    /// render; sublists share the hook. Rendering still stops at the first error, like C.
+    pub on_unresolved: Option<UnresolvedHook>,
 }
 
 impl Default for RenderContext {
@@ -47,7 +74,11 @@ impl Default for RenderContext {
             bounds: BoundingBox::new(),
             current_object: None,
             macros: HashMap::new(),
-            named_positions: HashMap::new(),
+            named_positions: Vec::new(),
+            on_object_placed: None,
+            print_lines: Vec::new(),
+            on_print: None,
+            on_unresolved: None,
         };
         ctx.init_builtin_variables();
         ctx
@@ -76,6 +107,7 @@ impl RenderContext {
             // Arc
             arcrad     => EvalValue::Length(Inches::from(0.25)),
             // Arrow
+            arrowhead  => EvalValue::Scalar(2.0),  // cref: aBuiltin[] - defined but never read by C
             arrowht    => EvalValue::Length(Inches::from(0.08)),  // C name
             arrowwid   => EvalValue::Length(Inches::from(0.06)),
             // Box
@@ -125,6 +157,47 @@ impl RenderContext {
         }
     }
 
+    /// Restore built-in variables to their defaults for the `reset` statement.
+    /// User `$variables`, objects and macros are kept.
+    pub fn reset_variables(&mut self) {
+        // fill and color have no built-in entry; removing them restores the defaults
+        self.variables.remove("fill");
+        self.variables.remove("color");
+        self.init_builtin_variables();
+    }
+
+    /// Render one statement into this context.
+    ///
+    /// Lets interactive tools build a diagram incrementally and inspect the
+    /// context (objects, variables, position) between statements.
+    /// `print` output is collected in `print_lines`.
+    pub fn render_statement(&mut self, stmt: &Statement) -> Result<(), PikruError> {
+        super::render_statement(self, stmt)
+    }
+
+    /// Number of top-level objects placed so far
+    pub fn object_count(&self) -> usize {
+        self.object_list.len()
+    }
+
+    /// Walk every placed object in placement order, descending into `[]` sublists.
+    /// Unlike `on_object_placed`, this also reaches sublist children, with their
+    /// final positions.
+    pub fn visit_objects(&self, mut f: impl FnMut(&RenderedObject)) {
+        for obj in &self.object_list {
+            obj.visit(&mut f);
+        }
+    }
+
+    /// Number of objects placed so far in this scope, optionally only of one class.
+    /// Backs the `count(...)` expression.
+    pub fn count_objects(&self, class: Option<ClassName>) -> usize {
+        self.object_list
+            .iter()
+            .filter(|o| class.map(|c| o.class() == c).unwrap_or(true))
+            .count()
+    }
+
     /// Get the last rendered object
     pub fn last_object(&self) -> Option<&RenderedObject> {
         self.object_list.last()
@@ -218,6 +291,10 @@ impl RenderContext {
         // Update bounds
         expand_object_bounds(&mut self.bounds, &obj);
 
+        if let Some(callback) = self.on_object_placed.as_mut() {
+            callback(&obj);
+        }
+
         // Update direction to match the object's direction
         // This handles cases like "arrow left" where the direction attribute
         // changes the global direction for subsequent objects
@@ -314,11 +391,22 @@ impl RenderContext {
             y = pos.y.raw(),
             "Adding named position"
         );
-        self.named_positions.insert(name, pos);
+        self.named_positions.push((name, pos));
     }
 
     /// Get a named position
     pub fn get_named_position(&self, name: &str) -> Option<PointIn> {
-        self.named_positions.get(name).copied()
+        self.named_positions
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, pos)| *pos)
+    }
+
+    /// Pass a reference that failed to resolve to the `on_unresolved` hook, if any
+    pub(crate) fn report_unresolved(&self, name: &str) {
+        if let Some(callback) = &self.on_unresolved {
+            callback(name);
+        }
     }
 }
@@ -19,3 +19,12 @@ pub const ARROW_WID: Inches = Inches::inches(0.06); // arrowwid
 pub const FONT_SIZE: f64 = 0.14;
 pub const MARGIN: f64 = 0.0;
 pub const CHARWID: f64 = 0.08;
+// animate_reveal timings, in seconds
+pub const REVEAL_DURATION: f64 = 0.5;
+pub const REVEAL_STEP: f64 = 0.3;
+// embed_font family name
+pub const EMBEDDED_FONT_FAMILY: &str = "pikru-embedded";
+// Vertices per line path (size of C's aTPath)
+pub const MAX_PATH_POINTS: usize = 1000;
+// data-pikchr-date: MANIFEST_ISODATE of the vendored C pikchr
+pub const PIKCHR_DATE: &str = "20250512153813";
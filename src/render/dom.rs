@@ -0,0 +1,201 @@
+//! SVG element types serialized with facet_xml
+//!
+//! These mirror the `facet_svg` elements pikru emits. `facet_svg` writes path data and
+//! polygon points with its own 3-decimal rounding, so here `d` and `points` are plain
+//! strings, formatted like C's `%g` when the element is built.
+
+use facet::Facet;
+use facet_svg::facet_xml as xml;
+
+pub use facet_svg::Style;
+
+/// Root `<svg>` element
+#[derive(Facet, Debug, Clone, Default)]
+#[facet(
+    xml::ns_all = "http://www.w3.org/2000/svg",
+    rename = "svg",
+    rename_all = "camelCase",
+    skip_all_unless_truthy
+)]
+pub struct Svg {
+    #[facet(xml::attribute)]
+    pub width: Option<String>,
+    #[facet(xml::attribute)]
+    pub height: Option<String>,
+    #[facet(xml::attribute)]
+    pub view_box: Option<String>,
+    #[facet(xml::attribute, rename = "data-pikchr-date")]
+    pub data_pikchr_date: Option<String>,
+    #[facet(flatten)]
+    pub children: Vec<SvgNode>,
+}
+
+/// Any element pikru emits
+#[derive(Facet, Debug, Clone)]
+#[facet(xml::ns_all = "http://www.w3.org/2000/svg")]
+#[repr(u8)]
+pub enum SvgNode {
+    G(Group),
+    Style(Style),
+    Circle(Circle),
+    Ellipse(Ellipse),
+    Path(Path),
+    Polygon(Polygon),
+    Text(Text),
+}
+
+/// `<g>` element
+#[derive(Facet, Debug, Clone, Default)]
+#[facet(xml::ns_all = "http://www.w3.org/2000/svg", skip_all_unless_truthy)]
+pub struct Group {
+    #[facet(xml::attribute)]
+    pub class: Option<String>,
+    #[facet(flatten)]
+    pub children: Vec<SvgNode>,
+}
+
+/// `<circle>` element
+#[derive(Facet, Debug, Clone, Default)]
+#[facet(
+    xml::ns_all = "http://www.w3.org/2000/svg",
+    rename_all = "kebab-case",
+    skip_all_unless_truthy
+)]
+pub struct Circle {
+    #[facet(xml::attribute)]
+    pub cx: Option<f64>,
+    #[facet(xml::attribute)]
+    pub cy: Option<f64>,
+    #[facet(xml::attribute)]
+    pub r: Option<f64>,
+    #[facet(xml::attribute)]
+    pub fill: Option<String>,
+    #[facet(xml::attribute)]
+    pub stroke: Option<String>,
+    #[facet(xml::attribute)]
+    pub stroke_width: Option<String>,
+    #[facet(xml::attribute)]
+    pub stroke_dasharray: Option<String>,
+    #[facet(xml::attribute)]
+    pub style: Option<String>,
+    #[facet(xml::attribute)]
+    pub data_layer: Option<String>,
+}
+
+/// `<ellipse>` element
+#[derive(Facet, Debug, Clone, Default)]
+#[facet(
+    xml::ns_all = "http://www.w3.org/2000/svg",
+    rename_all = "kebab-case",
+    skip_all_unless_truthy
+)]
+pub struct Ellipse {
+    #[facet(xml::attribute)]
+    pub cx: Option<f64>,
+    #[facet(xml::attribute)]
+    pub cy: Option<f64>,
+    #[facet(xml::attribute)]
+    pub rx: Option<f64>,
+    #[facet(xml::attribute)]
+    pub ry: Option<f64>,
+    #[facet(xml::attribute)]
+    pub fill: Option<String>,
+    #[facet(xml::attribute)]
+    pub stroke: Option<String>,
+    #[facet(xml::attribute)]
+    pub stroke_width: Option<String>,
+    #[facet(xml::attribute)]
+    pub stroke_dasharray: Option<String>,
+    #[facet(xml::attribute)]
+    pub style: Option<String>,
+    #[facet(xml::attribute)]
+    pub data_layer: Option<String>,
+}
+
+/// `<path>` element; `d` comes from `fmt_path_data`
+#[derive(Facet, Debug, Clone, Default)]
+#[facet(
+    xml::ns_all = "http://www.w3.org/2000/svg",
+    rename_all = "kebab-case",
+    skip_all_unless_truthy
+)]
+pub struct Path {
+    #[facet(xml::attribute)]
+    pub d: Option<String>,
+    #[facet(xml::attribute)]
+    pub fill: Option<String>,
+    #[facet(xml::attribute)]
+    pub stroke: Option<String>,
+    #[facet(xml::attribute)]
+    pub stroke_width: Option<String>,
+    #[facet(xml::attribute)]
+    pub stroke_dasharray: Option<String>,
+    #[facet(xml::attribute)]
+    pub style: Option<String>,
+    #[facet(xml::attribute)]
+    pub data_layer: Option<String>,
+}
+
+/// `<polygon>` element; `points` comes from `fmt_points`
+#[derive(Facet, Debug, Clone, Default)]
+#[facet(
+    xml::ns_all = "http://www.w3.org/2000/svg",
+    rename_all = "kebab-case",
+    skip_all_unless_truthy
+)]
+pub struct Polygon {
+    #[facet(xml::attribute)]
+    pub points: Option<String>,
+    #[facet(xml::attribute)]
+    pub fill: Option<String>,
+    #[facet(xml::attribute)]
+    pub stroke: Option<String>,
+    #[facet(xml::attribute)]
+    pub stroke_width: Option<String>,
+    #[facet(xml::attribute)]
+    pub stroke_dasharray: Option<String>,
+    #[facet(xml::attribute)]
+    pub style: Option<String>,
+    #[facet(xml::attribute)]
+    pub data_layer: Option<String>,
+}
+
+/// `<text>` element
+#[derive(Facet, Debug, Clone, Default)]
+#[facet(
+    xml::ns_all = "http://www.w3.org/2000/svg",
+    rename_all = "kebab-case",
+    skip_all_unless_truthy
+)]
+pub struct Text {
+    #[facet(xml::attribute)]
+    pub x: Option<f64>,
+    #[facet(xml::attribute)]
+    pub y: Option<f64>,
+    #[facet(xml::attribute)]
+    pub transform: Option<String>,
+    #[facet(xml::attribute)]
+    pub fill: Option<String>,
+    #[facet(xml::attribute)]
+    pub stroke: Option<String>,
+    #[facet(xml::attribute)]
+    pub stroke_width: Option<String>,
+    #[facet(xml::attribute)]
+    pub style: Option<String>,
+    #[facet(xml::attribute)]
+    pub data_layer: Option<String>,
+    #[facet(xml::attribute)]
+    pub font_family: Option<String>,
+    #[facet(xml::attribute)]
+    pub font_style: Option<String>,
+    #[facet(xml::attribute)]
+    pub font_weight: Option<String>,
+    #[facet(xml::attribute)]
+    pub font_size: Option<String>,
+    #[facet(xml::attribute)]
+    pub text_anchor: Option<String>,
+    #[facet(xml::attribute)]
+    pub dominant_baseline: Option<String>,
+    #[facet(xml::text)]
+    pub content: Option<String>,
+}
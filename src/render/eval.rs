@@ -34,45 +34,46 @@ impl From<f32> for Inches {
 }
 
 /// Get the nth vertex of a rendered object (line, spline, etc.)
-/// cref: pikchr "Nth vertex of object" syntax
-/// For line/spline objects, returns the nth waypoint (1-indexed)
-/// Falls back to start/end/center for objects without waypoints
-fn get_nth_vertex(obj: &RenderedObject, nth: &Nth) -> PointIn {
-    // Try to get waypoints from the object
-    if let Some(waypoints) = obj.waypoints() {
-        let len = waypoints.len();
-        if len == 0 {
-            return obj.center();
-        }
-
-        let index = match nth {
-            Nth::First(_) | Nth::Ordinal(1, _, _) => 0,
-            Nth::Last(_) | Nth::Previous(_) => len - 1,
-            Nth::Ordinal(n, _, _) => {
-                // Pikchr uses 1-based indexing
-                let idx = (*n as usize).saturating_sub(1);
-                idx.min(len - 1)
-            }
-        };
-
-        crate::log::debug!(
-            nth = ?nth,
-            waypoints_len = len,
-            index = index,
-            vertex_x = waypoints[index].x.raw(),
-            vertex_y = waypoints[index].y.raw(),
-            "get_nth_vertex"
-        );
-
-        waypoints[index]
-    } else {
-        // Fallback for non-line objects
-        match nth {
-            Nth::First(_) | Nth::Ordinal(1, _, _) => obj.start(),
-            Nth::Last(_) | Nth::Previous(_) => obj.end(),
-            Nth::Ordinal(_, _, _) => obj.center(),
+/// For line/spline objects, returns the nth waypoint (1-indexed). Arcs and moves
+/// only have their start and end as vertices.
+// cref: pik_nth_vertex (pikchr.c:6861-6875)
+fn get_nth_vertex(obj: &RenderedObject, nth: &Nth) -> Result<PointIn, PikruError> {
+    let endpoints;
+    let vertices = match obj.waypoints() {
+        Some(waypoints) => waypoints,
+        None if matches!(obj.class(), ClassName::Arc | ClassName::Move) => {
+            endpoints = [obj.start(), obj.end()];
+            &endpoints[..]
         }
-    }
+        None => {
+            return Err(PikruError::Generic("object is not a line".to_string()));
+        }
+    };
+    let len = vertices.len();
+
+    let index = match nth {
+        Nth::First(_) => Some(0),
+        Nth::Last(_) | Nth::Previous(_) => len.checked_sub(1),
+        // Pikchr uses 1-based indexing
+        Nth::Ordinal(n, _, _) => (*n as usize).checked_sub(1),
+    };
+    let Some(index) = index.filter(|&i| i < len) else {
+        return Err(PikruError::Generic(format!(
+            "no such vertex (the object has {} vertices)",
+            len
+        )));
+    };
+
+    crate::log::debug!(
+        nth = ?nth,
+        waypoints_len = len,
+        index = index,
+        vertex_x = vertices[index].x.raw(),
+        vertex_y = vertices[index].y.raw(),
+        "get_nth_vertex"
+    );
+
+    Ok(vertices[index])
 }
 
 pub fn eval_expr(ctx: &RenderContext, expr: &Expr) -> Result<Value, PikruError> {
@@ -113,6 +114,7 @@ pub fn eval_expr(ctx: &RenderContext, expr: &Expr) -> Result<Value, PikruError>
                         return Ok(Value::from(EvalValue::Color(color_val)));
                     }
                 }
+                ctx.report_unresolved(name);
                 Err(PikruError::Generic(format!("Undefined variable: {}", name)))
             }
         }
@@ -265,6 +267,33 @@ pub fn eval_expr(ctx: &RenderContext, expr: &Expr) -> Result<Value, PikruError>
                         ));
                     }
                 },
+                Function::Exp => {
+                    let v = match args[0] {
+                        Len(l) => l.raw(),
+                        Scalar(s) => s,
+                        Color(_) => {
+                            return Err(PikruError::Generic(
+                                "Cannot take exp() of a color".to_string(),
+                            ));
+                        }
+                    };
+                    Scalar(v.exp())
+                }
+                Function::Log => {
+                    let v = match args[0] {
+                        Len(l) => l.raw(),
+                        Scalar(s) => s,
+                        Color(_) => {
+                            return Err(PikruError::Generic(
+                                "Cannot take log() of a color".to_string(),
+                            ));
+                        }
+                    };
+                    if v <= 0.0 {
+                        return Err(PikruError::Generic("log of non-positive".to_string()));
+                    }
+                    Scalar(v.ln())
+                }
                 Function::Max => {
                     let a = match args[0] {
                         Len(l) => l.raw(),
@@ -307,6 +336,29 @@ pub fn eval_expr(ctx: &RenderContext, expr: &Expr) -> Result<Value, PikruError>
                     };
                     Scalar(a.min(b))
                 }
+                // Angles are in degrees to match cos()/sin(), measured counter-clockwise
+                // from the +x axis (not a compass heading)
+                Function::Atan2 => {
+                    let y = match args[0] {
+                        Len(l) => l.raw(),
+                        Scalar(s) => s,
+                        Color(_) => {
+                            return Err(PikruError::Generic(
+                                "Cannot take atan2() of a color".to_string(),
+                            ));
+                        }
+                    };
+                    let x = match args[1] {
+                        Len(l) => l.raw(),
+                        Scalar(s) => s,
+                        Color(_) => {
+                            return Err(PikruError::Generic(
+                                "Cannot take atan2() of a color".to_string(),
+                            ));
+                        }
+                    };
+                    Scalar(y.atan2(x).to_degrees())
+                }
             };
             validate_value(result)
         }
@@ -318,18 +370,20 @@ pub fn eval_expr(ctx: &RenderContext, expr: &Expr) -> Result<Value, PikruError>
             let dist = Inches::inches((offset.dx.raw().powi(2) + offset.dy.raw().powi(2)).sqrt());
             Ok(Value::Len(dist))
         }
+        Expr::CountCall(class) => {
+            let oc = class.as_ref().and_then(nth_class_to_class_name);
+            Ok(Value::Scalar(ctx.count_objects(oc) as f64))
+        }
         Expr::ObjectProp(obj, prop_ref) => {
-            let r = resolve_object(ctx, obj).ok_or_else(|| {
-                PikruError::Generic("Unknown object in property lookup".to_string())
-            })?;
+            let r = resolve_object(ctx, obj).ok_or_else(|| no_such_object(ctx, obj))?;
             match prop_ref {
                 PropertyRef::Num(prop) => {
                     let val = match prop {
                         NumProperty::Width => r.width(),
                         NumProperty::Height => r.height(),
-                        NumProperty::Radius | NumProperty::Diameter => {
-                            r.width().min(r.height()) / 2.0
-                        }
+                        NumProperty::Radius => r.width().min(r.height()) / 2.0,
+                        // cref: pik_property_of (pikchr.y:4179)
+                        NumProperty::Diameter => r.width().min(r.height()),
                         NumProperty::Thickness => r.style().stroke_width,
                     };
                     Ok(Value::Len(val))
@@ -350,20 +404,22 @@ pub fn eval_expr(ctx: &RenderContext, expr: &Expr) -> Result<Value, PikruError>
                     let rgb = color.to_u32();
                     Ok(Value::Scalar(rgb as f64))
                 }
+                PropertyRef::NPoints => {
+                    let n = r.waypoints().map_or(0, |w| w.len());
+                    Ok(Value::Scalar(n as f64))
+                }
             }
         }
         Expr::ObjectCoord(obj, coord) => {
-            let r = resolve_object(ctx, obj)
-                .ok_or_else(|| PikruError::Generic("Unknown object in coord lookup".to_string()))?;
+            // Also reaches named positions, e.g. `AS.y` after `AS: start of last arrow`
+            let pt = eval_place(ctx, &Place::Object(obj.clone()))?;
             Ok(Value::Len(match coord {
-                Coord::X => r.center().x,
-                Coord::Y => r.center().y,
+                Coord::X => pt.x,
+                Coord::Y => pt.y,
             }))
         }
         Expr::ObjectEdgeCoord(obj, edge, coord) => {
-            let r = resolve_object(ctx, obj).ok_or_else(|| {
-                PikruError::Generic("Unknown object in edge coord lookup".to_string())
-            })?;
+            let r = resolve_object(ctx, obj).ok_or_else(|| no_such_object(ctx, obj))?;
             let pt = get_edge_point(r, edge);
             Ok(Value::Len(match coord {
                 Coord::X => pt.x,
@@ -371,15 +427,20 @@ pub fn eval_expr(ctx: &RenderContext, expr: &Expr) -> Result<Value, PikruError>
             }))
         }
         Expr::VertexCoord(nth, obj, coord) => {
-            let r = resolve_object(ctx, obj).ok_or_else(|| {
-                PikruError::Generic("Unknown object in vertex coord lookup".to_string())
-            })?;
-            let target = get_nth_vertex(r, nth);
+            let r = resolve_object(ctx, obj).ok_or_else(|| no_such_object(ctx, obj))?;
+            let target = get_nth_vertex(r, nth)?;
             Ok(Value::Len(match coord {
                 Coord::X => target.x,
                 Coord::Y => target.y,
             }))
         }
+        Expr::PositionCoord(pos, coord) => {
+            let pt = eval_position(ctx, pos)?;
+            Ok(Value::Len(match coord {
+                Coord::X => pt.x,
+                Coord::Y => pt.y,
+            }))
+        }
         Expr::PlaceName(name) => Err(PikruError::Generic(format!(
             "Unsupported place name in expression: {}",
             name
@@ -445,7 +506,19 @@ pub fn eval_rvalue(ctx: &RenderContext, rvalue: &RValue) -> Result<EvalValue, Pi
     }
 }
 
+/// Evaluate a position, rejecting coordinates that overflowed to infinity or NaN
+/// (e.g. `1e308 right of (1e308, 0)`) instead of letting them reach the viewBox
 pub fn eval_position(ctx: &RenderContext, pos: &Position) -> Result<PointIn, PikruError> {
+    let point = eval_position_unchecked(ctx, pos)?;
+    if !point.x.is_finite() || !point.y.is_finite() {
+        return Err(PikruError::Generic(
+            "Arithmetic overflow in position (coordinate is infinite or NaN)".to_string(),
+        ));
+    }
+    Ok(point)
+}
+
+fn eval_position_unchecked(ctx: &RenderContext, pos: &Position) -> Result<PointIn, PikruError> {
     match pos {
         Position::Coords(x, y) => {
             let px = eval_len(ctx, x)?;
@@ -473,7 +546,8 @@ pub fn eval_position(ctx: &RenderContext, pos: &Position) -> Result<PointIn, Pik
                 _ => Ok(base),
             }
         }
-        Position::Between(factor, pos1, pos2) => {
+        // cref: pik_position_between (pikchr.y:715-718) - both forms share one rule
+        Position::Between(factor, pos1, pos2) | Position::Bracket(factor, pos1, pos2) => {
             let f = eval_scalar(ctx, factor)?;
             let p1 = eval_position(ctx, pos1)?;
             let p2 = eval_position(ctx, pos2)?;
@@ -491,13 +565,6 @@ pub fn eval_position(ctx: &RenderContext, pos: &Position) -> Result<PointIn, Pik
             );
             Ok(result)
         }
-        Position::Bracket(factor, pos1, pos2) => {
-            // Same as between: p1 + (p2 - p1) * f
-            let f = eval_scalar(ctx, factor)?;
-            let p1 = eval_position(ctx, pos1)?;
-            let p2 = eval_position(ctx, pos2)?;
-            Ok(p1 + (p2 - p1) * f)
-        }
         Position::AboveBelow(dist, ab, base_pos) => {
             let d = eval_len(ctx, dist)?;
             let base = eval_position(ctx, base_pos)?;
@@ -529,7 +596,7 @@ pub fn eval_position(ctx: &RenderContext, pos: &Position) -> Result<PointIn, Pik
             let base = eval_position(ctx, base_pos)?;
             let angle = match heading {
                 HeadingDir::EdgePoint(ep) => ep.to_angle(),
-                HeadingDir::Expr(e) => Angle::degrees(eval_scalar(ctx, e).unwrap_or(0.0)),
+                HeadingDir::Expr(e) => Angle::degrees(eval_scalar(ctx, e)?),
             };
             // C pikchr uses: pt.x += dist*sin(r); pt.y += dist*cos(r);
             // We use the same Y-up convention internally, flip happens in to_svg().
@@ -574,24 +641,7 @@ fn endpoint_object_from_place(ctx: &RenderContext, place: &Place) -> Option<Endp
         // Only return object for center references (e.g., `C0`, `last box`)
         // cref: pik_last_ref_object (pikchr.c) - only returns object if point == ptAt (center)
         // Edge references like `C0.ne` or `.ne of C0` do NOT trigger autochop
-        Place::Object(obj) => {
-            // cref: pik_position_from_place (pikchr.c)
-            // When referencing objects inside sublists (dotted names like Ptr.A),
-            // C pikchr does NOT trigger implicit autochop (both endpoints present).
-            // However, explicit `chop` attribute DOES work for dotted names.
-            // We mark dotted names so the autochop logic can differentiate.
-            if let Object::Named(name) = obj
-                && !name.path.is_empty()
-            {
-                // Object is inside a sublist (e.g., Ptr.A) - mark as dotted name
-                crate::log::debug!(
-                    ?name,
-                    "endpoint_object_from_place: dotted name (explicit chop works, implicit autochop disabled)"
-                );
-                return resolve_object(ctx, obj).map(EndpointObject::from_rendered_dotted);
-            }
-            resolve_object(ctx, obj).map(EndpointObject::from_rendered)
-        }
+        Place::Object(obj) => resolve_object(ctx, obj).map(EndpointObject::from_rendered),
         // Edge/vertex references do NOT set object attachment for autochop
         // cref: pik_last_ref_object checks ptAt == pPt, which fails for edge points
         Place::ObjectEdge(_, _) | Place::EdgePointOf(_, _) | Place::Vertex(_, _) => None,
@@ -617,7 +667,7 @@ fn eval_place(ctx: &RenderContext, place: &Place) -> Result<PointIn, PikruError>
                     );
                     return Ok(pos);
                 }
-                Ok(ctx.position)
+                Err(no_such_object(ctx, obj))
             }
         }
         Place::ObjectEdge(obj, edge) => {
@@ -633,22 +683,76 @@ fn eval_place(ctx: &RenderContext, place: &Place) -> Result<PointIn, PikruError>
                 );
                 Ok(edge_point)
             } else {
-                Ok(ctx.position)
+                Err(no_such_object(ctx, obj))
             }
         }
         Place::EdgePointOf(edge, obj) => {
-            if let Some(rendered) = resolve_object(ctx, obj) {
-                Ok(get_edge_point(rendered, edge))
-            } else {
-                Ok(ctx.position)
-            }
+            let rendered = resolve_object(ctx, obj).ok_or_else(|| no_such_object(ctx, obj))?;
+            Ok(get_edge_point(rendered, edge))
         }
         Place::Vertex(nth, obj) => {
-            if let Some(rendered) = resolve_object(ctx, obj) {
-                Ok(get_nth_vertex(rendered, nth))
-            } else {
-                Ok(ctx.position)
+            let rendered = resolve_object(ctx, obj).ok_or_else(|| no_such_object(ctx, obj))?;
+            get_nth_vertex(rendered, nth)
+        }
+    }
+}
+
+/// A reference that names no object in the current scope. Objects are only visible
+/// once placed (no forward references, as in C), and sublists start with an empty
+/// object list, so `previous`, `last box` or `1st box` inside `[...]` never reach
+/// objects of the enclosing diagram.
+// cref: pik_find_nth (pikchr.y:4005), pik_find_byname (pikchr.y:4046)
+pub(crate) fn no_such_object(ctx: &RenderContext, obj: &Object) -> PikruError {
+    let name = describe_object(obj);
+    ctx.report_unresolved(&name);
+    PikruError::Generic(format!("no such object: {}", name))
+}
+
+/// Spell an object reference the way it appears in source, for error messages
+fn describe_object(obj: &Object) -> String {
+    match obj {
+        Object::Named(name) => {
+            let mut s = match &name.base {
+                ObjectNameBase::This => "this".to_string(),
+                ObjectNameBase::PlaceName(n) => n.clone(),
+            };
+            for part in &name.path {
+                s.push('.');
+                s.push_str(part);
             }
+            s
+        }
+        Object::Nth(nth) => describe_nth(nth),
+        Object::NthOf(nth, container) => {
+            format!("{} of {}", describe_nth(nth), describe_object(container))
+        }
+    }
+}
+
+fn describe_nth(nth: &Nth) -> String {
+    let class = |class: &Option<NthClass>| match class {
+        Some(NthClass::ClassName(cn)) => format!(" {}", cn.keyword()),
+        Some(NthClass::Sublist) => " []".to_string(),
+        None => String::new(),
+    };
+    match nth {
+        Nth::First(c) => format!("first{}", class(c)),
+        Nth::Last(c) => format!("last{}", class(c)),
+        Nth::Previous(c) => format!("previous{}", class(c)),
+        Nth::Ordinal(n, modifier, c) => {
+            let suffix = match (n % 10, n % 100) {
+                (_, 11..=13) => "th",
+                (1, _) => "st",
+                (2, _) => "nd",
+                (3, _) => "rd",
+                _ => "th",
+            };
+            let modifier = match modifier {
+                NthModifier::None => "",
+                NthModifier::Last => " last",
+                NthModifier::Previous => " previous",
+            };
+            format!("{}{}{}{}", n, suffix, modifier, class(c))
         }
     }
 }
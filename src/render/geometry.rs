@@ -379,27 +379,8 @@ fn chop_against_ellipse(center: DVec2, half_size: DVec2, toward: DVec2) -> Optio
     Some(center + delta * scale)
 }
 
-/// Render an oval (pill shape)
-/// Render a rounded box as a path (matching C pikchr output)
 /// Create a rounded box path using PathData fluent API (matching C pikchr output)
-pub fn create_rounded_box_path(x1: f64, y1: f64, x2: f64, y2: f64, r: f64) -> PathData {
-    // C pikchr path format for rounded box:
-    // Start at bottom-left corner (after radius), go clockwise
-    PathData::new()
-        .m(x1 + r, y2) // M: start bottom-left after radius
-        .l(x2 - r, y2) // L: line to bottom-right before radius
-        .a(r, r, 0.0, false, false, x2, y2 - r) // A: arc to right edge
-        .l(x2, y1 + r) // L: line up to top-right before radius
-        .a(r, r, 0.0, false, false, x2 - r, y1) // A: arc to top edge
-        .l(x1 + r, y1) // L: line left to top-left after radius
-        .a(r, r, 0.0, false, false, x1, y1 + r) // A: arc to left edge
-        .l(x1, y2 - r) // L: line down to bottom-left before radius
-        .a(r, r, 0.0, false, false, x1 + r, y2) // A: arc back to start
-        .z() // Z: close path
-}
-
-/// Create oval (pill shape) path using PathData fluent API (matching C pikchr output)
-/// Oval has fully rounded ends where rad = min(width, height) / 2
+/// Used for boxes with rad > 0 and for ovals, which have rad = min(width, height) / 2
 /// cref: boxRender (oval uses same render function as box with rad > 0)
 pub fn create_oval_path(x1: f64, y1: f64, x2: f64, y2: f64, rad: f64) -> PathData {
     // IMPORTANT: The path must go COUNTER-CLOCKWISE with sweep-flag=0 for arcs
@@ -477,7 +458,7 @@ pub fn create_cylinder_paths_with_rad(
     let rx = width / 2.0;
     let h2 = height / 2.0;
 
-    // C pikchr cylinder path format:
+    // C pikchr cylinder path format (cref: cylinderRender, pikchr.y:1314-1319):
     // M left,top  L left,bottom  A bottom-arc  L right,top  A top-back-arc  A top-front-arc
     let top_y = cy - h2 + ry;
     let bottom_y = cy + h2 - ry;
@@ -677,6 +658,8 @@ pub fn arc_control_point(clockwise: bool, from: DVec2, to: DVec2) -> DVec2 {
 ///
 /// C pikchr renders arcs as quadratic bezier curves, NOT as SVG arc commands.
 /// This gives more predictable curves that match the original implementation.
+/// The curve depends only on the endpoints and direction: `arc from A to B rad 1`
+/// is accepted but the radius does not change the shape, exactly like C.
 pub fn create_arc_path(start: DVec2, end: DVec2, clockwise: bool) -> PathData {
     let control = arc_control_point(clockwise, start, end);
 
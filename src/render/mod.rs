@@ -4,12 +4,15 @@
 //! - `defaults`: Default sizes and settings
 //! - `types`: Core types like Value, PositionedText, RenderedObject, ClassName, ObjectStyle
 //! - `context`: RenderContext for tracking state during rendering
+//! - `entities`: HTML named entities, converted to characters in text output
 //! - `eval`: Expression evaluation functions
 //! - `geometry`: Chop functions and path creation
 //! - `svg`: SVG generation
 
 pub mod context;
 pub mod defaults;
+pub mod dom;
+pub mod entities;
 pub mod eval;
 pub mod geometry;
 pub mod path_builder;
@@ -23,16 +26,16 @@ pub use shapes::Shape;
 pub use types::*;
 
 use crate::ast::*;
-use crate::errors::PikruError;
-use crate::types::{EvalValue, Length as Inches, OffsetIn, Point};
+use crate::errors::{EvalError, PikruError};
+use crate::types::{Color, EvalValue, Length as Inches, OffsetIn, Point};
 use eval::{
     endpoint_object_from_position, eval_color, eval_expr, eval_len, eval_position, eval_rvalue,
-    eval_scalar, resolve_object,
+    eval_scalar, no_such_object, resolve_object,
 };
 use svg::generate_svg;
 
 /// Options for rendering pikchr to SVG
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone)]
 pub struct RenderOptions {
     /// Emit CSS variables for colors instead of direct color values.
     /// When enabled, generates a `<style>` block with all colors defined using `light-dark()`.
@@ -42,6 +45,89 @@ pub struct RenderOptions {
     /// This prevents inline SVGs from scaling up to fill their container.
     /// The dimensions are computed using ceiling to avoid clipping.
     pub explicit_size: bool,
+    /// Progressively reveal the diagram in browsers (pikru extension).
+    /// Each top-level object fades in after the previous one, in placement order,
+    /// via a CSS `@keyframes` animation and a per-object `animation-delay`.
+    pub animate_reveal: bool,
+    /// Translate the diagram so the viewBox starts at `0 0` (default, like C pikchr).
+    /// When disabled, coordinates stay in diagram space (Y flipped, in pixels) and the
+    /// viewBox carries the non-zero origin instead, which is handy for tiling.
+    pub translate_origin: bool,
+    /// Embed a font file (pikru extension) so text renders the same everywhere.
+    /// The bytes are base64-encoded into an `@font-face` rule in a `<style>` block,
+    /// and all non-monospace text uses it. WOFF, WOFF2, TrueType and OpenType are
+    /// recognized from their magic bytes and declared with the matching `format()`.
+    pub embed_font: Option<Vec<u8>>,
+    /// Tag each object's elements with the layer used to order them (pikru extension),
+    /// as a `data-layer="<n>"` attribute, to debug `behind` and the `layer` variable.
+    /// Sublist children carry their sublist's layer.
+    pub emit_layers: bool,
+    /// Indent the SVG with one element per line, for reading and diffing.
+    /// Off by default, which keeps the compact output compared against C pikchr.
+    pub pretty: bool,
+    /// Add the `data-pikchr-date` attribute C pikchr puts on the root `<svg>`.
+    /// The value is the release date of the C version pikru tracks, not the render time.
+    /// Releases before 1.1 have no such attribute, see [`CompatLevel::Pikchr1_0`].
+    pub pikchr_date: bool,
+    /// Which C pikchr release to reproduce where their output differs.
+    pub compat: CompatLevel,
+    /// Wrap each arrow's path, arrowheads and label in a `<g class="arrow">` (pikru
+    /// extension), so a stylesheet can restyle whole arrows. Any line, spline or arc
+    /// with a head counts as an arrow.
+    pub group_arrows: bool,
+    /// Paint the whole viewBox in this color behind the diagram (pikru extension),
+    /// for diagrams embedded on pages with another background. The bounding box
+    /// is unchanged. `None` (the default) leaves the SVG transparent, like C pikchr.
+    pub background: Option<Color>,
+    /// Resolve `include "file"` statements (pikru extension; C pikchr has no includes).
+    /// Files are read relative to the including file, so this only takes effect in
+    /// [`crate::pikchr_from_path`]; everywhere else an `include` is an error.
+    pub resolve_includes: bool,
+    /// Layout direction before the first `up`/`down`/`left`/`right` statement.
+    /// C pikchr always starts going right, which is the default.
+    pub initial_direction: Direction,
+    /// Write `print` output to stderr as each statement runs (pikru extension), instead
+    /// of ahead of the SVG. An `on_print` hook already set on the context takes precedence.
+    pub print_to_stderr: bool,
+}
+
+/// A C pikchr release whose output quirks pikru reproduces.
+/// Differences are taken from C pikchr's changelog (`doc/changelog.md`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompatLevel {
+    /// Pikchr 1.0 (2024-04-01): the root `<svg>` has no `data-pikchr-date`.
+    Pikchr1_0,
+    /// Pikchr 1.1 (unreleased): the check-in pikru tracks, see `vendor/pikchr-c`.
+    #[default]
+    Pikchr1_1,
+}
+
+impl CompatLevel {
+    /// Whether the root `<svg>` can carry `data-pikchr-date` (added in 1.1)
+    pub fn has_date_attribute(self) -> bool {
+        self != CompatLevel::Pikchr1_0
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            css_variables: false,
+            explicit_size: false,
+            animate_reveal: false,
+            translate_origin: true,
+            embed_font: None,
+            emit_layers: false,
+            pretty: false,
+            pikchr_date: false,
+            compat: CompatLevel::default(),
+            group_arrows: false,
+            background: None,
+            resolve_includes: false,
+            initial_direction: Direction::Right,
+            print_to_stderr: false,
+        }
+    }
 }
 
 // TODO: Move these to appropriate submodules
@@ -188,27 +274,43 @@ pub fn render_with_options(
     program: &Program,
     options: &RenderOptions,
 ) -> Result<String, PikruError> {
-    let mut ctx = RenderContext::new();
-    let mut print_lines: Vec<String> = Vec::new();
+    render_with_context(program, options, RenderContext::new())
+}
+
+/// Render a pikchr program to SVG using a caller-provided context
+///
+/// This lets callers install hooks such as `RenderContext::on_object_placed`
+/// before rendering starts.
+pub fn render_with_context(
+    program: &Program,
+    options: &RenderOptions,
+    mut ctx: RenderContext,
+) -> Result<String, PikruError> {
+    // cref: pikchr (pikchr.y:5358) - C always starts with DIR_RIGHT
+    ctx.direction = options.initial_direction;
+    if options.print_to_stderr && ctx.on_print.is_none() {
+        ctx.on_print = Some(Box::new(|line| eprintln!("{}", line)));
+    }
 
     // Process all statements
     for stmt in &program.statements {
-        render_statement(&mut ctx, stmt, &mut print_lines)?;
+        ctx.render_statement(stmt)?;
     }
 
-    // If there are print lines and no drawables, emit print output (HTML with <br>)
-    if ctx.object_list.is_empty() && !print_lines.is_empty() {
-        let mut out = String::new();
-        for line in print_lines {
-            out.push_str(&line);
-            out.push_str("<br>\n");
-        }
-        return Ok(out);
+    // Print output (HTML with <br>) comes before the SVG
+    // cref: print/pritem (pikchr.y:601-609) - items are appended to the output as parsed
+    let mut out = String::new();
+    for line in std::mem::take(&mut ctx.print_lines) {
+        out.push_str(&line);
+        out.push_str("<br>\n");
     }
 
     // If nothing was drawn and no prints, emit empty comment like C
     if ctx.object_list.is_empty() {
-        return Ok("<!-- empty pikchr diagram -->\n".to_string());
+        if out.is_empty() {
+            out.push_str("<!-- empty pikchr diagram -->\n");
+        }
+        return Ok(out);
     }
 
     crate::log::debug!(
@@ -220,14 +322,11 @@ pub fn render_with_options(
     );
 
     // Generate SVG
-    generate_svg(&ctx, options)
+    out.push_str(&generate_svg(&ctx, options)?);
+    Ok(out)
 }
 
-fn render_statement(
-    ctx: &mut RenderContext,
-    stmt: &Statement,
-    print_lines: &mut Vec<String>,
-) -> Result<(), PikruError> {
+fn render_statement(ctx: &mut RenderContext, stmt: &Statement) -> Result<(), PikruError> {
     match stmt {
         Statement::Direction(dir) => {
             // cref: pik_set_direction (pikchr.c:5746)
@@ -263,6 +362,9 @@ fn render_statement(
             // cref: pik_set_var (pikchr.c:6479-6511)
             // eval_rvalue now returns EvalValue directly, preserving Color type information
             let rhs_val = eval_rvalue(ctx, &assign.rvalue)?;
+            if matches!(assign.lvalue, LValue::Fill) {
+                check_fill_value(assign, &rhs_val)?;
+            }
 
             // Get variable name for lookup
             let var_name = match &assign.lvalue {
@@ -275,61 +377,14 @@ fn render_statement(
             // Apply compound assignment operators
             let eval_val = match assign.op {
                 AssignOp::Assign => rhs_val,
-                AssignOp::AddAssign
-                | AssignOp::SubAssign
-                | AssignOp::MulAssign
-                | AssignOp::DivAssign => {
+                _ => {
                     // Get current value (with default of 0)
                     let current = ctx
                         .variables
                         .get(&var_name)
                         .cloned()
                         .unwrap_or(EvalValue::Scalar(0.0));
-
-                    // Apply operation based on types
-                    match (current, rhs_val) {
-                        (EvalValue::Length(lhs), EvalValue::Scalar(rhs)) => {
-                            // Length op Scalar
-                            let result = match assign.op {
-                                AssignOp::AddAssign => lhs + Inches(rhs),
-                                AssignOp::SubAssign => lhs - Inches(rhs),
-                                AssignOp::MulAssign => lhs * rhs,
-                                AssignOp::DivAssign => lhs / rhs,
-                                _ => unreachable!(),
-                            };
-                            EvalValue::Length(result)
-                        }
-                        (EvalValue::Scalar(lhs), EvalValue::Scalar(rhs)) => {
-                            // Scalar op Scalar
-                            let result = match assign.op {
-                                AssignOp::AddAssign => lhs + rhs,
-                                AssignOp::SubAssign => lhs - rhs,
-                                AssignOp::MulAssign => lhs * rhs,
-                                AssignOp::DivAssign => {
-                                    if rhs == 0.0 {
-                                        lhs
-                                    } else {
-                                        lhs / rhs
-                                    }
-                                }
-                                _ => unreachable!(),
-                            };
-                            EvalValue::Scalar(result)
-                        }
-                        (EvalValue::Length(lhs), EvalValue::Length(rhs)) => {
-                            // For *= and /=, treat RHS length as scalar (bare number context)
-                            // cref: pik_set_var (pikchr.c:6496-6499) - *= and /= use raw values
-                            let result = match assign.op {
-                                AssignOp::AddAssign => lhs + rhs,
-                                AssignOp::SubAssign => lhs - rhs,
-                                AssignOp::MulAssign => lhs * rhs.raw(), // Treat as scalar for multiplication
-                                AssignOp::DivAssign => lhs / rhs.raw(), // Treat as scalar for division
-                                _ => lhs,
-                            };
-                            EvalValue::Length(result)
-                        }
-                        _ => rhs_val, // Fallback for other combinations
-                    }
+                    apply_compound_assign(&var_name, assign.op, current, rhs_val)?
                 }
             };
 
@@ -387,11 +442,13 @@ fn render_statement(
                 };
                 parts.push(s);
             }
-            print_lines.push(parts.join(" "));
-        }
-        Statement::Assert(_) => {
-            // Not rendered
+            let line = parts.join(" ");
+            match ctx.on_print.as_mut() {
+                Some(hook) => hook(&line),
+                None => ctx.print_lines.push(line),
+            }
         }
+        Statement::Assert(assert) => check_assert(ctx, assert)?,
         Statement::Define(def) => {
             // Store macro definition (later definitions override earlier ones)
             // Strip the surrounding braces from the body
@@ -409,7 +466,7 @@ fn render_statement(
                 // Parse and render the macro body
                 let parsed = crate::parse::parse(&body)?;
                 for inner_stmt in &parsed.statements {
-                    render_statement(ctx, inner_stmt, print_lines)?;
+                    render_statement(ctx, inner_stmt)?;
                 }
             }
             // If macro not found, treat as custom object type (ignore for now)
@@ -418,10 +475,110 @@ fn render_statement(
             // Error statement produces an intentional error
             return Err(PikruError::Generic(format!("error: {}", e.message)));
         }
+        Statement::Reset => ctx.reset_variables(),
+        Statement::Include(include) => return Err(unresolved_include(include)),
     }
     Ok(())
 }
 
+/// Error for an `include` that reached rendering: only [`crate::pikchr_from_path`]
+/// knows where files live, and it resolves them only when asked to
+fn unresolved_include(include: &IncludeStmt) -> PikruError {
+    PikruError::Generic(format!(
+        "include \"{}\" is a pikru extension: render with pikchr_from_path and RenderOptions::resolve_includes",
+        include.path
+    ))
+}
+
+/// Apply a compound assignment (`+=`, `-=`, `*=`, `/=`) to a variable's current value.
+///
+/// C stores colors as plain numbers, so `$x = red; $x += 1` silently yields a
+/// meaningless value. pikru rejects compound assignments that involve a color on
+/// either side instead; lengths and scalars combine like they do in C.
+// cref: pik_set_var (pikchr.c:6479-6511)
+fn apply_compound_assign(
+    name: &str,
+    op: AssignOp,
+    current: EvalValue,
+    rhs: EvalValue,
+) -> Result<EvalValue, PikruError> {
+    let op_str = match op {
+        AssignOp::Assign => return Ok(rhs),
+        AssignOp::AddAssign => "+=",
+        AssignOp::SubAssign => "-=",
+        AssignOp::MulAssign => "*=",
+        AssignOp::DivAssign => "/=",
+    };
+    match (current, rhs) {
+        (EvalValue::Color(_), _) => Err(PikruError::Generic(format!(
+            "{} {}: cannot apply arithmetic to a color",
+            name, op_str
+        ))),
+        (_, EvalValue::Color(_)) => Err(PikruError::Generic(format!(
+            "{} {}: cannot combine a number with a color",
+            name, op_str
+        ))),
+        (EvalValue::Length(lhs), EvalValue::Scalar(rhs)) => Ok(EvalValue::Length(match op {
+            AssignOp::AddAssign => lhs + Inches(rhs),
+            AssignOp::SubAssign => lhs - Inches(rhs),
+            AssignOp::MulAssign => lhs * rhs,
+            _ => lhs / rhs,
+        })),
+        (EvalValue::Scalar(lhs), EvalValue::Scalar(rhs)) => Ok(EvalValue::Scalar(match op {
+            AssignOp::AddAssign => lhs + rhs,
+            AssignOp::SubAssign => lhs - rhs,
+            AssignOp::MulAssign => lhs * rhs,
+            _ if rhs == 0.0 => lhs,
+            _ => lhs / rhs,
+        })),
+        // For *= and /=, treat RHS length as scalar (bare number context)
+        // cref: pik_set_var (pikchr.c:6496-6499) - *= and /= use raw values
+        (EvalValue::Length(lhs), EvalValue::Length(rhs)) => Ok(EvalValue::Length(match op {
+            AssignOp::AddAssign => lhs + rhs,
+            AssignOp::SubAssign => lhs - rhs,
+            AssignOp::MulAssign => lhs * rhs.raw(),
+            _ => lhs / rhs.raw(),
+        })),
+        // A scalar combined with a length takes on the length's unit
+        (EvalValue::Scalar(lhs), EvalValue::Length(rhs)) => Ok(EvalValue::Length(match op {
+            AssignOp::AddAssign => Inches(lhs) + rhs,
+            AssignOp::SubAssign => Inches(lhs) - rhs,
+            AssignOp::MulAssign => rhs * lhs,
+            _ => Inches(lhs / rhs.raw()),
+        })),
+    }
+}
+
+/// Reject a `fill = ...` value that cannot be used as a fill color.
+///
+/// Unknown color names evaluate to `Scalar(0.0)`, which `render_object_stmt` would
+/// otherwise turn into "no fill" - so a typo like `fill = bisqe` silently produced
+/// unfilled shapes. `none`/`off` and numeric values are still accepted.
+fn check_fill_value(assign: &Assignment, value: &EvalValue) -> Result<(), PikruError> {
+    let span = assign.span;
+    match (&assign.rvalue, value) {
+        (_, EvalValue::Color(_)) => Ok(()),
+        (RValue::PlaceName(name), _) => {
+            if matches!(name.to_lowercase().as_str(), "none" | "off") {
+                Ok(())
+            } else {
+                Err(EvalError::UnknownColor {
+                    name: name.clone(),
+                    span,
+                }
+                .into())
+            }
+        }
+        (RValue::Expr(_), EvalValue::Length(_)) => Err(EvalError::TypeMismatch {
+            expected: "color",
+            got: "length",
+            span,
+        }
+        .into()),
+        (RValue::Expr(_), EvalValue::Scalar(_)) => Ok(()),
+    }
+}
+
 /// Expand a bounding box to include a rendered object (recursing into sublists)
 // cref: pik_bbox_add_elist (pikchr.c:7206) - iterates objects
 // cref: pik_bbox_add_elist (pikchr.c:7243) - checks pObj->sw>=0.0 before adding bbox
@@ -767,9 +924,9 @@ fn render_object_stmt(
     // Unwrap for use in fit/position logic - Sublist uses default Box-like behavior
     let class = class_name.unwrap_or(ClassName::Box);
 
-    // Get layer from "layer" variable, default 1000
-    // cref: pik_elem_new (pikchr.c:2960)
-    let mut layer = ctx.get_scalar("layer", 1000.0) as i32;
+    // Get layer from "layer" variable, default 1000, never below 0
+    // cref: pik_elem_new (pikchr.c:2960-2962)
+    let mut layer = (ctx.get_scalar("layer", 1000.0) as i32).max(0);
 
     // Determine base object properties from context variables (like C pikchr's pik_value)
     let (mut width, mut height) = match &obj_stmt.basetype {
@@ -836,7 +993,7 @@ fn render_object_stmt(
             // Use proportional character widths like C pikchr
             let charwid = ctx.get_scalar("charwid", 0.08);
             let charht = ctx.get_scalar("charht", 0.14);
-            let pt = PositionedText::from_textposition(s.value.clone(), pos.as_ref());
+            let pt = PositionedText::from_textposition(ctx, s.value.clone(), pos.as_ref())?;
             let w = pt.width_inches(charwid);
             let h = pt.height(charht);
             (Inches(w), Inches(h))
@@ -850,7 +1007,11 @@ fn render_object_stmt(
     // For sublists, render children early to compute their actual bounds
     let (sublist_children, sublist_bounds) =
         if let BaseType::Sublist(statements) = &obj_stmt.basetype {
-            let children = render_sublist(ctx, statements)?;
+            // C keeps one layout direction for the whole diagram, so a direction
+            // set inside `[...]` carries on after it, and places the sublist itself
+            // cref: savelist (pikchr.y:625) saves only the list, pik_set_direction (pikchr.y:3060)
+            let (children, direction) = render_sublist(ctx, statements)?;
+            ctx.direction = direction;
             let bounds = compute_children_bounds(&children);
             (Some(children), Some(bounds))
         } else {
@@ -943,7 +1104,9 @@ fn render_object_stmt(
         /// cref: pik_evenwith (pikchr.c) - sets x or y based on mTPath flag
         /// For horizontal directions (Left/Right): set X = target.X, keep current Y
         /// For vertical directions (Up/Down): set Y = target.Y, keep current X
-        EvenWith(Direction, PointIn),
+        /// The flag is false when the clause only sets the other coordinate of the
+        /// previous point (`then down 1 right until even with B`), like C's mTPath check
+        EvenWith(Direction, PointIn, bool),
         /// Heading: move at arbitrary angle (degrees clockwise from north)
         /// cref: pik_move_hdg (pikchr.c:3323-3365)
         Heading(Inches, f64),
@@ -966,9 +1129,10 @@ fn render_object_stmt(
     // Extract text from basetype
     if let BaseType::Text(s, pos) = &obj_stmt.basetype {
         text.push(PositionedText::from_textposition(
+            ctx,
             s.value.clone(),
             pos.as_ref(),
-        ));
+        )?);
     }
 
     // Default arrow style for arrows
@@ -994,6 +1158,14 @@ fn render_object_stmt(
     for attr in &obj_stmt.attributes {
         match attr {
             Attribute::NumProperty(prop, relexpr) => {
+                // cref: sublistInit (pikchr.y:1764) - a sublist's width, height and radius
+                // are computed from its children, so pik_param_ok rejects setting them
+                if class_name == Some(ClassName::Sublist) && *prop != NumProperty::Thickness {
+                    return Err(PikruError::Generic(
+                        "value already fixed by prior constraints: a [] sublist is sized by its contents"
+                            .to_string(),
+                    ));
+                }
                 let raw_val = eval_len(ctx, &relexpr.expr)?;
                 // If percent, multiply by current value (or default) to get actual value
                 let val = if relexpr.is_percent {
@@ -1090,7 +1262,9 @@ fn render_object_stmt(
             }
             Attribute::ColorProperty(prop, rvalue) => {
                 let color = eval_color(ctx, rvalue);
-                // cref: dotNumProp (pikchr.c:1353-1363) - dots keep fill and stroke synchronized
+                // cref: pik_set_clrprop (pikchr.c:5876-5892) - `color` only sets the stroke and `fill`
+                // only sets the fill. The sole exception is dotNumProp (pikchr.c:4031-4041):
+                // dots copy whichever was just set into the other.
                 match prop {
                     ColorProperty::Fill => {
                         style.fill = color.clone();
@@ -1139,58 +1313,55 @@ fn render_object_stmt(
                     // cref: pikchr.y:693,696 - invis sets sw to negative, solid resets to positive
                     // This effectively clears invisibility when solid is applied
                     style.invisible = false;
-                    style.stroke_width = ctx
-                        .variables
-                        .get("thickness")
-                        .and_then(|v| match v {
-                            EvalValue::Length(l) => Some(*l),
-                            _ => None,
-                        })
-                        .unwrap_or(defaults::STROKE_WIDTH);
+                    style.dashed = None;
+                    style.dotted = None;
+                    // `thickness = 0.02` is stored as a scalar, so read it like any length
+                    style.stroke_width = Inches::inches(eval::get_length(
+                        ctx,
+                        "thickness",
+                        defaults::STROKE_WIDTH.raw(),
+                    ));
                 }
                 BoolProperty::Clockwise => style.clockwise = true,
                 BoolProperty::CounterClockwise => style.clockwise = false,
             },
             Attribute::StringAttr(s, pos) => {
                 text.push(PositionedText::from_textposition(
+                    ctx,
                     s.value.clone(),
                     pos.as_ref(),
-                ));
+                )?);
             }
             Attribute::At(pos) => {
                 crate::log::debug!(?pos, "Attribute::At position");
-                if let Ok(p) = eval_position(ctx, pos) {
-                    crate::log::debug!(x = p.x.0, y = p.y.0, "Attribute::At evaluated");
-                    explicit_position = Some(p);
-                }
+                let p = eval_position(ctx, pos)?;
+                crate::log::debug!(x = p.x.0, y = p.y.0, "Attribute::At evaluated");
+                explicit_position = Some(p);
             }
             Attribute::From(pos) => {
-                if let Ok(p) = eval_position(ctx, pos) {
-                    from_position = Some(p);
-                    if from_attachment.is_none() {
-                        from_attachment = endpoint_object_from_position(ctx, pos);
-                    }
+                from_position = Some(eval_position(ctx, pos)?);
+                if from_attachment.is_none() {
+                    from_attachment = endpoint_object_from_position(ctx, pos);
                 }
             }
             Attribute::To(pos) => {
-                if let Ok(p) = eval_position(ctx, pos) {
-                    crate::log::debug!(x = p.x.0, y = p.y.0, "Attribute::To evaluated position");
-                    to_positions.push(p);
-                    if to_attachment.is_none() {
-                        to_attachment = endpoint_object_from_position(ctx, pos);
-                    }
-                    // cref: pik_add_to (pikchr.y:3464) overwrites current path point
-                    // If there's a pending then segment direction, the "to" position
-                    // replaces it rather than adding to it. Clear the pending segment.
-                    if in_then_segment {
-                        current_segment_offset = OffsetIn::ZERO;
-                        current_segment_direction = None;
-                        in_then_segment = false;
-                    }
-                    // cref: pik_reset_samepath (pikchr.c:5923-5928)
-                    // Explicit "to" position resets any path copied from "same"
-                    same_path_waypoints = None;
+                let p = eval_position(ctx, pos)?;
+                crate::log::debug!(x = p.x.0, y = p.y.0, "Attribute::To evaluated position");
+                to_positions.push(p);
+                if to_attachment.is_none() {
+                    to_attachment = endpoint_object_from_position(ctx, pos);
+                }
+                // cref: pik_add_to (pikchr.y:3464) overwrites current path point
+                // If there's a pending then segment direction, the "to" position
+                // replaces it rather than adding to it. Clear the pending segment.
+                if in_then_segment {
+                    current_segment_offset = OffsetIn::ZERO;
+                    current_segment_direction = None;
+                    in_then_segment = false;
                 }
+                // cref: pik_reset_samepath (pikchr.c:5923-5928)
+                // Explicit "to" position resets any path copied from "same"
+                same_path_waypoints = None;
             }
             Attribute::DirectionMove(_go, dir, dist) => {
                 has_direction_move = true;
@@ -1200,19 +1371,24 @@ fn render_object_stmt(
                 // Update object's direction - this will become the new global direction
                 // cref: pik_after_adding_element sets p->eDir = pObj->outDir
                 object_direction = *dir;
+                // cref: pik_add_direction (pikchr.y:3292) - up/down scale the height
+                let along = match dir {
+                    Direction::Right | Direction::Left => width,
+                    Direction::Up | Direction::Down => height,
+                };
                 let distance = if let Some(relexpr) = dist {
                     if let Ok(d) = eval_len(ctx, &relexpr.expr) {
-                        // Handle percent: 40% means 40% of the default line width
+                        // Handle percent: 40% means 40% of the default line length
                         if relexpr.is_percent {
-                            width * (d.raw() / 100.0)
+                            along * (d.raw() / 100.0)
                         } else {
                             d
                         }
                     } else {
-                        width // default distance
+                        along // default distance
                     }
                 } else {
-                    width // default distance
+                    along // default distance
                 };
                 // cref: pik_add_direction (pikchr.c:3272) - accumulates directions
                 // If we're in a then segment, accumulate to current segment
@@ -1224,17 +1400,38 @@ fn render_object_stmt(
                     direction_offset += dir.offset(distance);
                 }
             }
-            Attribute::DirectionEven(_go, dir, pos) => {
-                // cref: pik_reset_samepath (pikchr.c:5923-5928)
-                // Even-with clauses reset any path copied from "same"
-                same_path_waypoints = None;
-                even_clause = Some((*dir, pos.clone()));
-            }
-            Attribute::DirectionUntilEven(_go, dir, pos) => {
+            Attribute::DirectionEven(_go, dir, pos)
+            | Attribute::DirectionUntilEven(_go, dir, pos) => {
                 // cref: pik_reset_samepath (pikchr.c:5923-5928)
                 // Even-with clauses reset any path copied from "same"
                 same_path_waypoints = None;
-                even_clause = Some((*dir, pos.clone()));
+                if in_then_segment || current_segment_direction.is_some() || !segments.is_empty() {
+                    // cref: pik_evenwith (pikchr.y:3374-3400)
+                    // After a "then" the clause applies to the current path point. It only
+                    // starts a new point if that coordinate was already set on it.
+                    let horizontal = matches!(dir, Direction::Left | Direction::Right);
+                    let mut new_point = true;
+                    if let Some(segment_dir) = current_segment_direction.take() {
+                        new_point = if horizontal {
+                            current_segment_offset.dx.raw() != 0.0
+                        } else {
+                            current_segment_offset.dy.raw() != 0.0
+                        };
+                        segments.push(Segment::Offset(current_segment_offset, segment_dir));
+                        current_segment_offset = OffsetIn::ZERO;
+                    } else if !in_then_segment
+                        && let Some(Segment::EvenWith(last_dir, _, _)) = segments.last()
+                    {
+                        new_point =
+                            matches!(last_dir, Direction::Left | Direction::Right) == horizontal;
+                    }
+                    let target = eval_position(ctx, pos)?;
+                    segments.push(Segment::EvenWith(*dir, target, new_point));
+                    object_direction = *dir;
+                    in_then_segment = false;
+                } else {
+                    even_clause = Some((*dir, pos.clone()));
+                }
             }
             Attribute::CompassMove(dist, edgept) => {
                 has_direction_move = true;
@@ -1292,7 +1489,7 @@ fn render_object_stmt(
                 // cref: pik_reset_samepath (pikchr.c:5923-5928)
                 // Heading moves reset any path copied from "same"
                 same_path_waypoints = None;
-                let angle = eval_scalar(ctx, angle_expr).unwrap_or(0.0);
+                let angle = eval_scalar(ctx, angle_expr)?;
                 let distance = if let Some(relexpr) = opt_dist {
                     let d = eval_len(ctx, &relexpr.expr).unwrap_or(width);
                     if relexpr.is_percent {
@@ -1344,18 +1541,22 @@ fn render_object_stmt(
                 // Process the then clause's direction if it has one
                 match clause {
                     ThenClause::DirectionMove(dir, dist) => {
+                        let along = match dir {
+                            Direction::Right | Direction::Left => width,
+                            Direction::Up | Direction::Down => height,
+                        };
                         let distance = if let Some(relexpr) = dist {
                             if let Ok(d) = eval_len(ctx, &relexpr.expr) {
                                 if relexpr.is_percent {
-                                    width * (d.raw() / 100.0)
+                                    along * (d.raw() / 100.0)
                                 } else {
                                     d
                                 }
                             } else {
-                                width
+                                along
                             }
                         } else {
-                            width
+                            along
                         };
                         current_segment_offset += dir.offset(distance);
                         current_segment_direction = Some(*dir);
@@ -1392,9 +1593,8 @@ fn render_object_stmt(
                             current_segment_offset = OffsetIn::ZERO;
                             current_segment_direction = None;
                         }
-                        if let Ok(p) = eval_position(ctx, pos) {
-                            segments.push(Segment::AbsolutePosition(p));
-                        }
+                        let p = eval_position(ctx, pos)?;
+                        segments.push(Segment::AbsolutePosition(p));
                         // cref: pik_add_to sets pTo for autochop
                         // When "then to <object>" is used, set to_attachment for autochop
                         if to_attachment.is_none() {
@@ -1415,9 +1615,8 @@ fn render_object_stmt(
                             current_segment_offset = OffsetIn::ZERO;
                             current_segment_direction = None;
                         }
-                        if let Ok(target) = eval_position(ctx, pos) {
-                            segments.push(Segment::EvenWith(*dir, target));
-                        }
+                        let target = eval_position(ctx, pos)?;
+                        segments.push(Segment::EvenWith(*dir, target, true));
                         object_direction = *dir;
                         in_then_segment = false;
                     }
@@ -1432,7 +1631,7 @@ fn render_object_stmt(
                             current_segment_offset = OffsetIn::ZERO;
                             current_segment_direction = None;
                         }
-                        let angle = eval_scalar(ctx, angle_expr).unwrap_or(0.0);
+                        let angle = eval_scalar(ctx, angle_expr)?;
                         let distance = if let Some(relexpr) = opt_dist {
                             let d = eval_len(ctx, &relexpr.expr).unwrap_or(width);
                             if relexpr.is_percent {
@@ -1466,12 +1665,26 @@ fn render_object_stmt(
             }
             Attribute::Fit => {
                 // cref: pik_size_to_fit (pikchr.c:3754-3782)
-                // Compute fit using current state (text, width, height) just like C does
-                style.fit = true;
+                // Compute fit using current state (text, width, height) just like C does.
+                // Classes without an xFit (lines, arcs, dots, moves and [] sublists,
+                // whose size always comes from their contents) ignore `fit`.
+                let has_fit = matches!(
+                    class_name,
+                    Some(ClassName::Box)
+                        | Some(ClassName::Circle)
+                        | Some(ClassName::Cylinder)
+                        | Some(ClassName::Diamond)
+                        | Some(ClassName::Ellipse)
+                        | Some(ClassName::File)
+                        | Some(ClassName::Oval)
+                        | Some(ClassName::Text)
+                );
 
-                if !text.is_empty() {
-                    let charwid = ctx.get_scalar("charwid", defaults::CHARWID);
+                if has_fit && !text.is_empty() {
+                    style.fit = true;
+                    // cref: pik_compute_layout_settings (pikchr.y:4562-4563) - both scale by fontscale
                     let fontscale = ctx.get_scalar("fontscale", 1.0);
+                    let charwid = ctx.get_scalar("charwid", defaults::CHARWID) * fontscale;
                     let charht = ctx.get_scalar("charht", defaults::FONT_SIZE) * fontscale;
                     let sw = style.stroke_width.raw();
 
@@ -1557,7 +1770,7 @@ fn render_object_stmt(
                             TextVSlot::Below => -(0.5 * hc + 0.5 * hb1),
                             TextVSlot::Below2 => -(0.5 * hc + hb1 + 0.5 * hb2),
                         };
-                        let y = y_base + y_offset;
+                        let y = y_base + y_offset + t.shift;
                         let ch = charht * 0.5 * t.font_scale();
                         bbox_min_y = bbox_min_y.min(y - ch);
                         bbox_max_y = bbox_max_y.max(y + ch);
@@ -1615,9 +1828,10 @@ fn render_object_stmt(
                             height = Inches(h);
                         }
                         Some(ClassName::File) => {
-                            let rad = ctx.get_length("filerad", 0.15);
+                            // cref: fileFit (pikchr.y:1538) - uses the object's own rad,
+                            // which an earlier `rad` attribute may have changed
                             width = fit_width;
-                            height = fit_height + rad * 2.0;
+                            height = fit_height + style.corner_radius * 2.0;
                         }
                         Some(ClassName::Oval) => {
                             width = fit_width.max(fit_height);
@@ -1635,7 +1849,9 @@ fn render_object_stmt(
                 // Copy properties from referenced object
                 // cref: pik_same (pikchr.c:6761-6804)
                 let source = match obj_ref {
-                    Some(obj) => resolve_object(ctx, obj),
+                    Some(obj) => {
+                        Some(resolve_object(ctx, obj).ok_or_else(|| no_such_object(ctx, obj))?)
+                    }
                     None => ctx.get_last_object(Some(class)),
                 };
                 if let Some(source) = source {
@@ -1676,10 +1892,20 @@ fn render_object_stmt(
                             width = source.width();
                             height = source.height();
                         }
+                        // C draws a circle from the copied rad, which is zero for most
+                        // other classes; keep it round with the source's width as diameter
+                        if class == ClassName::Circle {
+                            height = width;
+                        }
                     }
                     // Always copy style properties
                     // cref: pik_same (pikchr.c:6792-6803)
                     style = source.style().clone();
+                    // Round objects keep their rad in their size rather than the style,
+                    // so `box same as C` gets the circle's radius for its corners
+                    if matches!(source.class_name, ClassName::Circle | ClassName::Dot) {
+                        style.corner_radius = source.width() / 2.0;
+                    }
 
                     // Copy layer for z-ordering
                     // This ensures "box same" after "box behind X" inherits the layer
@@ -1694,16 +1920,16 @@ fn render_object_stmt(
                 let edge = match &clause.edge {
                     WithEdge::DotEdge(ep) | WithEdge::EdgePoint(ep) => *ep,
                 };
-                if let Ok(target) = eval_position(ctx, &clause.position) {
-                    with_clause = Some((edge, target));
-                }
+                let target = eval_position(ctx, &clause.position)?;
+                with_clause = Some((edge, target));
             }
             Attribute::Behind(obj_ref) => {
                 // Lower the layer of the current object so that it is behind the given object
                 // cref: pik_behind (pikchr.c:3500-3505)
-                if let Some(other) = resolve_object(ctx, obj_ref) {
-                    // Set our layer to one less than the other object's layer
-                    // We'll apply this after creating the object
+                let other =
+                    resolve_object(ctx, obj_ref).ok_or_else(|| no_such_object(ctx, obj_ref))?;
+                // Only ever lower the layer, so `behind A behind B` ends up behind both
+                if layer >= other.layer {
                     layer = other.layer - 1;
                 }
             }
@@ -1717,8 +1943,9 @@ fn render_object_stmt(
         let needs_autofit_width = width.raw() <= 0.0;
 
         if needs_autofit_height || needs_autofit_width {
-            let charwid = ctx.get_scalar("charwid", defaults::CHARWID);
+            // cref: pik_compute_layout_settings (pikchr.y:4562-4563) - both scale by fontscale
             let fontscale = ctx.get_scalar("fontscale", 1.0);
+            let charwid = ctx.get_scalar("charwid", defaults::CHARWID) * fontscale;
             let charht = ctx.get_scalar("charht", defaults::FONT_SIZE) * fontscale;
             let sw = style.stroke_width.raw();
 
@@ -1799,7 +2026,8 @@ fn render_object_stmt(
                         TextVSlot::Center => 0.0,
                         TextVSlot::Below => -(0.5 * hc + 0.5 * hb1),
                         TextVSlot::Below2 => -(0.5 * hc + hb1 + 0.5 * hb2),
-                    };
+                    }
+                    + t.shift;
 
                 let nx = if t.ljust {
                     -jw
@@ -1914,8 +2142,9 @@ fn render_object_stmt(
     // Normal fit is handled inline when Attribute::Fit is encountered
     let should_fit = class == ClassName::Text && !style.fit;
     if should_fit && !text.is_empty() {
-        let charwid = ctx.get_scalar("charwid", defaults::CHARWID);
+        // cref: pik_compute_layout_settings (pikchr.y:4562-4563) - both scale by fontscale
         let fontscale = ctx.get_scalar("fontscale", 1.0);
+        let charwid = ctx.get_scalar("charwid", defaults::CHARWID) * fontscale;
         let charht = ctx.get_scalar("charht", defaults::FONT_SIZE) * fontscale;
 
         // For box-style shapes (eJust=1), C computes bbox with jw-based offsets
@@ -2020,7 +2249,8 @@ fn render_object_stmt(
                     TextVSlot::Center => 0.0,
                     TextVSlot::Below => -(0.5 * hc + 0.5 * hb1),
                     TextVSlot::Below2 => -(0.5 * hc + hb1 + 0.5 * hb2),
-                };
+                }
+                + t.shift;
             // Character half-height, scaled by font scale
             let ch = charht * 0.5 * t.font_scale();
             // Text extends from y-ch to y+ch
@@ -2131,7 +2361,7 @@ fn render_object_stmt(
         same_path = same_path_waypoints.is_some(),
         "position branch conditions"
     );
-    let (center, start, end, waypoints) = if from_position.is_some()
+    let (center, mut start, mut end, waypoints) = if from_position.is_some()
         || !to_positions.is_empty()
         || has_direction_move
         || !segments.is_empty()
@@ -2290,7 +2520,7 @@ fn render_object_stmt(
             {
                 // from X to Y [to Z...] - add all to_positions as waypoints
                 for pos in &to_positions {
-                    points.push(*pos);
+                    push_path_point(&mut points, *pos);
                 }
             } else if has_direction_move || !segments.is_empty() || even_clause.is_some() {
                 // cref: C pikchr accumulates directions per segment
@@ -2336,7 +2566,7 @@ fn render_object_stmt(
                 if !to_positions.is_empty() {
                     // Add all to_positions as waypoints
                     for pos in &to_positions {
-                        points.push(*pos);
+                        push_path_point(&mut points, *pos);
                         current_pos = *pos;
                     }
                 }
@@ -2399,7 +2629,11 @@ fn render_object_stmt(
                             );
                             *pos
                         }
-                        Segment::EvenWith(dir, target) => {
+                        Segment::EvenWith(dir, target, new_point) => {
+                            if !new_point {
+                                // Move the previous point instead of adding one
+                                points.pop();
+                            }
                             // cref: pik_evenwith (pikchr.c) - sets x or y based on mTPath flag
                             // For vertical directions (Up/Down), Y is being changed, so take Y from target
                             // For horizontal directions (Left/Right), X is being changed, so take X from target
@@ -2449,7 +2683,7 @@ fn render_object_stmt(
                             next
                         }
                     };
-                    points.push(next);
+                    push_path_point(&mut points, next);
                     current_pos = next;
                 }
             } else {
@@ -2599,26 +2833,16 @@ fn render_object_stmt(
     // Apply chopping to waypoints for line-like objects
     // cref: pik_after_adding_attributes (pikchr.c:4372-4379)
     // This modifies waypoints in place, matching C pikchr's behavior where
-    // chopping happens during construction, not rendering.
+    // chopping happens during construction, not rendering. Only the `chop`
+    // attribute sets bChop: a line between two object centers is not trimmed
+    // otherwise. Arcs and moves are line classes too.
     let mut waypoints = waypoints;
     let is_line_like = matches!(
         class,
-        ClassName::Line | ClassName::Arrow | ClassName::Spline
+        ClassName::Line | ClassName::Arrow | ClassName::Spline | ClassName::Arc | ClassName::Move
     );
-    // Implicit autochop: triggered when BOTH endpoints are objects AND neither is a dotted name
-    // cref: pik_position_from_place (pikchr.c) - doesn't set ppObj for dotted names
-    // Explicit chop: always allowed via style.chop, even for dotted names
-    let implicit_autochop = from_attachment
-        .as_ref()
-        .map(|a| !a.is_dotted_name)
-        .unwrap_or(false)
-        && to_attachment
-            .as_ref()
-            .map(|a| !a.is_dotted_name)
-            .unwrap_or(false);
-    let should_chop = style.chop || implicit_autochop;
-
-    if is_line_like && should_chop && waypoints.len() >= 2 {
+
+    if is_line_like && style.chop && waypoints.len() >= 2 {
         use geometry::autochop_inches;
         let n = waypoints.len();
 
@@ -2638,6 +2862,11 @@ fn render_object_stmt(
             waypoints[0] = autochop_inches(from_pt, to_pt, from_obj);
         }
 
+        // Arcs and moves are built from their endpoints rather than the path.
+        // An arc's control point is derived from the chopped ends, like arcRender
+        start = waypoints[0];
+        end = waypoints[n - 1];
+
         crate::log::debug!(
             start_x = waypoints[0].x.raw(),
             start_y = waypoints[0].y.raw(),
@@ -2806,15 +3035,63 @@ fn render_object_stmt(
     })
 }
 
-/// Render a sublist of statements with local coordinates and return children (still local)
+/// Fail with `lhs != rhs` unless both sides of an `assert` print the same.
+/// Like C, the sides are compared as %g strings, which absorbs rounding error.
+// cref: pik_assert (pikchr.y:2693), pik_position_assert (pikchr.y:2710)
+fn check_assert(ctx: &RenderContext, assert: &Assert) -> Result<(), PikruError> {
+    use svg::fmt_num;
+
+    let (lhs, rhs) = match &assert.condition {
+        AssertCondition::ExprEqual(lhs, rhs) => {
+            let side = |expr: &Expr| -> Result<String, PikruError> {
+                Ok(fmt_num(match eval_expr(ctx, expr)? {
+                    Value::Scalar(v) => v,
+                    Value::Len(l) => l.0,
+                    Value::Color(c) => c as f64,
+                }))
+            };
+            (side(lhs)?, side(rhs)?)
+        }
+        AssertCondition::PositionEqual(lhs, rhs) => {
+            let side = |pos: &Position| -> Result<String, PikruError> {
+                let p = eval_position(ctx, pos)?;
+                Ok(format!("({},{})", fmt_num(p.x.0), fmt_num(p.y.0)))
+            };
+            (side(lhs)?, side(rhs)?)
+        }
+    };
+    if lhs != rhs {
+        return Err(PikruError::Generic(format!(
+            "assertion failed: {} != {}",
+            lhs, rhs
+        )));
+    }
+    Ok(())
+}
+
+/// Append a vertex to a line's path. Like C's fixed-size path buffer, a full path
+/// stops growing and keeps moving its last vertex, so the line still ends where
+/// the source says.
+// cref: pik_next_rpath (pikchr.y:3256)
+fn push_path_point(points: &mut Vec<PointIn>, point: PointIn) {
+    if points.len() < defaults::MAX_PATH_POINTS {
+        points.push(point);
+    } else if let Some(last) = points.last_mut() {
+        *last = point;
+    }
+}
+
+/// Render a sublist of statements with local coordinates and return children (still local),
+/// along with the layout direction in effect at the end of the sublist
 fn render_sublist(
     parent_ctx: &RenderContext,
     statements: &[Statement],
-) -> Result<Vec<RenderedObject>, PikruError> {
+) -> Result<(Vec<RenderedObject>, Direction), PikruError> {
     // Local context: starts at (0,0) but inherits variables and direction
     let mut ctx = RenderContext::new();
     ctx.direction = parent_ctx.direction;
     ctx.variables = parent_ctx.variables.clone();
+    ctx.on_unresolved = parent_ctx.on_unresolved.clone();
 
     for stmt in statements {
         match stmt {
@@ -2855,6 +3132,9 @@ fn render_sublist(
                 // cref: pik_set_var (pikchr.c:6479-6511)
                 // Variable assignments inside sublists should be processed locally
                 let rhs_val = eval_rvalue(&ctx, &assign.rvalue)?;
+                if matches!(assign.lvalue, LValue::Fill) {
+                    check_fill_value(assign, &rhs_val)?;
+                }
 
                 let var_name = match &assign.lvalue {
                     LValue::Variable(name) => name.clone(),
@@ -2865,55 +3145,13 @@ fn render_sublist(
 
                 let eval_val = match assign.op {
                     AssignOp::Assign => rhs_val,
-                    AssignOp::AddAssign
-                    | AssignOp::SubAssign
-                    | AssignOp::MulAssign
-                    | AssignOp::DivAssign => {
+                    _ => {
                         let current = ctx
                             .variables
                             .get(&var_name)
                             .cloned()
                             .unwrap_or(EvalValue::Scalar(0.0));
-
-                        match (current, rhs_val) {
-                            (EvalValue::Length(lhs), EvalValue::Scalar(rhs)) => {
-                                let result = match assign.op {
-                                    AssignOp::AddAssign => lhs + Inches(rhs),
-                                    AssignOp::SubAssign => lhs - Inches(rhs),
-                                    AssignOp::MulAssign => lhs * rhs,
-                                    AssignOp::DivAssign => lhs / rhs,
-                                    _ => unreachable!(),
-                                };
-                                EvalValue::Length(result)
-                            }
-                            (EvalValue::Scalar(lhs), EvalValue::Scalar(rhs)) => {
-                                let result = match assign.op {
-                                    AssignOp::AddAssign => lhs + rhs,
-                                    AssignOp::SubAssign => lhs - rhs,
-                                    AssignOp::MulAssign => lhs * rhs,
-                                    AssignOp::DivAssign => {
-                                        if rhs == 0.0 {
-                                            lhs
-                                        } else {
-                                            lhs / rhs
-                                        }
-                                    }
-                                    _ => unreachable!(),
-                                };
-                                EvalValue::Scalar(result)
-                            }
-                            (EvalValue::Length(lhs), EvalValue::Length(rhs)) => {
-                                let result = match assign.op {
-                                    AssignOp::AddAssign => lhs + rhs,
-                                    AssignOp::SubAssign => lhs - rhs,
-                                    AssignOp::MulAssign => lhs * rhs.raw(),
-                                    AssignOp::DivAssign => lhs / rhs.raw(),
-                                    _ => lhs,
-                                };
-                                EvalValue::Length(result)
-                            }
-                            _ => rhs_val,
-                        }
+                        apply_compound_assign(&var_name, assign.op, current, rhs_val)?
                     }
                 };
 
@@ -2925,13 +3163,16 @@ fn render_sublist(
                 );
                 ctx.variables.insert(var_name, eval_val);
             }
+            Statement::Reset => ctx.reset_variables(),
+            Statement::Assert(assert) => check_assert(&ctx, assert)?,
+            Statement::Include(include) => return Err(unresolved_include(include)),
             _ => {
                 // Skip other statement types in sublists (macros, etc.)
             }
         }
     }
 
-    Ok(ctx.object_list)
+    Ok((ctx.object_list, ctx.direction))
 }
 
 /// Calculate center position given that a specific edge should be at target
@@ -3063,7 +3304,11 @@ fn calculate_object_position(
     let (start, end, center) = match class {
         ClassName::Line | ClassName::Arrow | ClassName::Spline | ClassName::Move => {
             let start = ctx.position;
-            // Use linewid for horizontal (Right/Left), lineht for vertical (Up/Down)
+            // Use linewid for horizontal (Right/Left), lineht for vertical (Up/Down).
+            // When both `wid` and `ht` are given, only the one along the current
+            // direction is used - C never builds a diagonal from them, and the
+            // object's w/h are recomputed from the resulting path afterwards.
+            // cref: pik_after_adding_attributes (pikchr.c:7036-7045) - default path is one segment
             let length = match ctx.direction {
                 Direction::Right | Direction::Left => width,
                 Direction::Up | Direction::Down => height,
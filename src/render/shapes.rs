@@ -6,20 +6,20 @@
 //! - Render itself to SVG
 
 use crate::types::{BoxIn, Length as Inches, OffsetIn, Point, Scaler, Size, UnitVec};
-use facet_svg::{Circle as SvgCircle, Ellipse as SvgEllipse, Path, PathData, SvgNode};
+use facet_svg::PathData;
 use glam::DVec2;
 
 use super::defaults;
+use super::dom::{Circle as SvgCircle, Ellipse as SvgEllipse, Path, SvgNode};
 use super::{TextVSlot, compute_text_vslots, sum_text_heights_above_below};
 
 /// Bounding box type alias
 pub type BoundingBox = BoxIn;
 use super::geometry::{
-    arc_control_point, chop_line, create_arc_path_with_control, create_cylinder_paths_with_rad,
-    create_file_paths, create_line_path, create_oval_path, create_rounded_box_path,
-    create_spline_path,
+    arc_control_point, create_arc_path_with_control, create_cylinder_paths_with_rad,
+    create_file_paths, create_line_path, create_oval_path, create_spline_path,
 };
-use super::svg::{color_to_rgb, color_to_string, render_arrowhead_dom};
+use super::svg::{color_to_string, fmt_num, fmt_path_data, render_arrowhead_dom};
 use super::types::{ClassName, ObjectStyle, PointIn, PositionedText, RenderedObject};
 
 use enum_dispatch::enum_dispatch;
@@ -50,6 +50,25 @@ fn chop_point(from: DVec2, to: DVec2, amount: f64) -> DVec2 {
     from + delta * r
 }
 
+/// Draw an arrowhead at `to` pointing away from `from`, then pull `to` back by half
+/// the head's height so the stroke ends inside it. Heads are drawn one at a time, so
+/// a second head on the same segment sees the first one's chop. A segment shorter
+/// than the head shrinks the head to the segment's length.
+/// cref: pik_draw_arrowhead (pikchr.c:1977-2004)
+fn draw_arrowhead(
+    from: DVec2,
+    to: &mut DVec2,
+    style: &ObjectStyle,
+    arrow_len: f64,
+    arrow_width: f64,
+    use_css_vars: bool,
+) -> Option<SvgNode> {
+    let h = arrow_len.min((*to - from).length());
+    let head = render_arrowhead_dom(from, *to, style, h, arrow_width, use_css_vars)?;
+    *to = chop_point(from, *to, h / 2.0);
+    Some(SvgNode::Polygon(head))
+}
+
 /// Shorten a waypoint list from the start (for start arrows)
 /// cref: pik_chop (pikchr.c:1958-1970)
 fn chop_waypoint_start(waypoints: &mut [PointIn], amount: Inches) {
@@ -143,19 +162,16 @@ pub trait Shape {
                 | EdgeDirection::SouthEast
                 | EdgeDirection::SouthWest
         );
-        let diag = if is_diagonal {
-            if self.is_round() {
-                1.0
-            } else {
-                std::f64::consts::SQRT_2
-            }
+        // Use UnitVec for direction, scale x by hw and y by hh. Corners of a
+        // non-round shape take the signs alone: undoing the 1/√2 of the unit
+        // vector leaves rounding error where C computes exactly ±w/2, ±h/2.
+        let unit = direction.unit_vec();
+        let offset = if is_diagonal && !self.is_round() {
+            OffsetIn::new(hw * unit.dx().signum(), hh * unit.dy().signum())
         } else {
-            1.0
+            unit.scale_xy(hw, hh)
         };
 
-        // Use UnitVec for direction, scale x by hw and y by hh
-        let offset = direction.unit_vec().scale_xy(hw * diag, hh * diag);
-
         center + offset
     }
 
@@ -185,8 +201,17 @@ pub trait Shape {
     /// Expand a bounding box to include this shape's "core" bounds (without arrowheads).
     /// Used for computing sublist width/height (pObj->w/h in C).
     /// cref: pikchr.y:1757-1761 - sublist bbox computed from children's bbox (no arrowheads)
-    /// Default implementation calls expand_bounds; LineShape overrides to exclude arrowheads.
+    /// Default implementation adds the object's extent even when it is invisible (C adds
+    /// every child's bbox regardless of sw), then calls expand_bounds for text.
+    /// cref: sublistInit (pikchr.c:4437-4444)
     fn expand_core_bounds(&self, bounds: &mut BoundingBox) {
+        bounds.expand_rect(
+            self.center(),
+            Size {
+                w: self.width(),
+                h: self.height(),
+            },
+        );
         self.expand_bounds(bounds);
     }
 
@@ -201,8 +226,10 @@ pub trait Shape {
         let center = self.center();
 
         let old_min_x = bounds.min.x.0;
+        // cref: pik_bbox_add_elist (pikchr.c:7252) - negative thickness hides the shape too
+        let invisible = style.invisible || style.stroke_width.0 < 0.0;
 
-        if style.invisible && !text.is_empty() {
+        if invisible && !text.is_empty() {
             // For invisible objects, only include text bounds
             let charht = defaults::FONT_SIZE;
             let charwid = defaults::CHARWID;
@@ -213,7 +240,7 @@ pub trait Shape {
                 bounds.expand_point(Point::new(center.x - hw, center.y - hh));
                 bounds.expand_point(Point::new(center.x + hw, center.y + hh));
             }
-        } else if !style.invisible {
+        } else if !invisible {
             bounds.expand_rect(
                 center,
                 Size {
@@ -244,7 +271,7 @@ pub trait Shape {
             center_x = center.x.0,
             width = self.width().0,
             height = self.height().0,
-            invisible = style.invisible,
+            invisible,
             "[BBOX]"
         );
     }
@@ -363,6 +390,7 @@ impl Shape for CircleShape {
             stroke: None,
             stroke_width: None,
             stroke_dasharray: None,
+            data_layer: None,
             style: Some(svg_style),
         };
         nodes.push(SvgNode::Circle(circle));
@@ -502,19 +530,24 @@ impl Shape for BoxShape {
         let svg_style = build_svg_style(&self.style, ctx.scaler, ctx.dashwid, ctx.use_css_vars);
 
         let path_data = if self.corner_radius > Inches::ZERO {
-            let r = ctx.scaler.px(self.corner_radius);
-            create_rounded_box_path(x1, y1, x2, y2, r)
+            // cref: boxRender (pikchr.y:1202-1203) - radius is capped at half of either side
+            let rad = self
+                .corner_radius
+                .min(self.width / 2.0)
+                .min(self.height / 2.0);
+            create_oval_path(x1, y1, x2, y2, ctx.scaler.px(rad))
         } else {
             // Regular box: start bottom-left, go clockwise
             PathData::new().m(x1, y2).l(x2, y2).l(x2, y1).l(x1, y1).z()
         };
 
         let path = Path {
-            d: Some(path_data),
+            d: Some(fmt_path_data(&path_data)),
             fill: None,
             stroke: None,
             stroke_width: None,
             stroke_dasharray: None,
+            data_layer: None,
             style: Some(svg_style),
         };
         nodes.push(SvgNode::Path(path));
@@ -585,6 +618,7 @@ impl Shape for EllipseShape {
             stroke: None,
             stroke_width: None,
             stroke_dasharray: None,
+            data_layer: None,
             style: Some(svg_style),
         };
         nodes.push(SvgNode::Ellipse(ellipse));
@@ -695,11 +729,12 @@ impl Shape for OvalShape {
 
         let path_data = create_oval_path(x1, y1, x2, y2, rad);
         let path = Path {
-            d: Some(path_data),
+            d: Some(fmt_path_data(&path_data)),
             fill: None,
             stroke: None,
             stroke_width: None,
             stroke_dasharray: None,
+            data_layer: None,
             style: Some(svg_style),
         };
         nodes.push(SvgNode::Path(path));
@@ -814,11 +849,12 @@ impl Shape for DiamondShape {
             .z();
 
         let path = Path {
-            d: Some(path_data),
+            d: Some(fmt_path_data(&path_data)),
             fill: None,
             stroke: None,
             stroke_width: None,
             stroke_dasharray: None,
+            data_layer: None,
             style: Some(svg_style),
         };
         nodes.push(SvgNode::Path(path));
@@ -890,22 +926,24 @@ impl Shape for CylinderShape {
             create_cylinder_paths_with_rad(center_svg.x, center_svg.y, w, h, rad);
 
         let body = Path {
-            d: Some(body_path),
+            d: Some(fmt_path_data(&body_path)),
             fill: None,
             stroke: None,
             stroke_width: None,
             stroke_dasharray: None,
+            data_layer: None,
             style: Some(svg_style.clone()),
         };
         nodes.push(SvgNode::Path(body));
 
         if !bottom_arc_path.commands.is_empty() {
             let bottom_arc = Path {
-                d: Some(bottom_arc_path),
+                d: Some(fmt_path_data(&bottom_arc_path)),
                 fill: None,
                 stroke: None,
                 stroke_width: None,
                 stroke_dasharray: None,
+                data_layer: None,
                 style: Some(svg_style),
             };
             nodes.push(SvgNode::Path(bottom_arc));
@@ -1068,31 +1106,34 @@ impl Shape for FileShape {
         let (main_path, fold_path) = create_file_paths(center_svg.x, center_svg.y, w, h, rad);
 
         let main = Path {
-            d: Some(main_path),
+            d: Some(fmt_path_data(&main_path)),
             fill: None,
             stroke: None,
             stroke_width: None,
             stroke_dasharray: None,
+            data_layer: None,
             style: Some(svg_style.clone()),
         };
         nodes.push(SvgNode::Path(main));
 
-        // Fold line uses same style but no fill
-        let fold_style = svg_style_from_entries(vec![
-            ("fill", "none".to_string()),
-            ("stroke", color_to_rgb(&self.style.stroke)),
-            (
-                "stroke-width",
-                format!("{}", ctx.scaler.px(self.style.stroke_width)),
-            ),
-        ]);
+        // Fold line uses same style (including dashes) but no fill
+        // cref: fileRender (pikchr.y:1562) - pik_append_style(p,pObj,0)
+        let fold_style = build_svg_style_full(
+            &self.style,
+            ctx.scaler,
+            ctx.dashwid,
+            false,
+            false,
+            ctx.use_css_vars,
+        );
 
         let fold = Path {
-            d: Some(fold_path),
+            d: Some(fmt_path_data(&fold_path)),
             fill: None,
             stroke: None,
             stroke_width: None,
             stroke_dasharray: None,
+            data_layer: None,
             style: Some(fold_style),
         };
         nodes.push(SvgNode::Path(fold));
@@ -1248,7 +1289,6 @@ impl Shape for LineShape {
         };
         let arrow_len_px = ctx.scaler.px(ctx.arrow_len) * arrow_scale;
         let arrow_wid_px = ctx.scaler.px(ctx.arrow_wid) * arrow_scale;
-        let arrow_chop = arrow_len_px / 2.0;
 
         let mut svg_points: Vec<DVec2> = self
             .waypoints
@@ -1264,37 +1304,28 @@ impl Shape for LineShape {
 
             // cref: lineRender (pikchr.c:4271-4276) - larrow first, then rarrow
             if self.style.arrow_start
-                && let Some(arrowhead) = render_arrowhead_dom(
+                && let Some(arrowhead) = draw_arrowhead(
                     draw_end,
-                    draw_start,
+                    &mut draw_start,
                     &self.style,
                     arrow_len_px,
                     arrow_wid_px,
                     ctx.use_css_vars,
                 )
             {
-                nodes.push(SvgNode::Polygon(arrowhead));
+                nodes.push(arrowhead);
             }
             if self.style.arrow_end
-                && let Some(arrowhead) = render_arrowhead_dom(
+                && let Some(arrowhead) = draw_arrowhead(
                     draw_start,
-                    draw_end,
+                    &mut draw_end,
                     &self.style,
                     arrow_len_px,
                     arrow_wid_px,
                     ctx.use_css_vars,
                 )
             {
-                nodes.push(SvgNode::Polygon(arrowhead));
-            }
-
-            if self.style.arrow_start {
-                let (new_start, _) = chop_line(draw_start, draw_end, arrow_chop);
-                draw_start = new_start;
-            }
-            if self.style.arrow_end {
-                let (_, new_end) = chop_line(draw_start, draw_end, arrow_chop);
-                draw_end = new_end;
+                nodes.push(arrowhead);
             }
 
             let mut path_data = PathData::new()
@@ -1304,11 +1335,12 @@ impl Shape for LineShape {
                 path_data = path_data.z();
             }
             let path = Path {
-                d: Some(path_data),
+                d: Some(fmt_path_data(&path_data)),
                 fill: None,
                 stroke: None,
                 stroke_width: None,
                 stroke_dasharray: None,
+                data_layer: None,
                 style: Some(svg_style),
             };
             nodes.push(SvgNode::Path(path));
@@ -1328,39 +1360,30 @@ impl Shape for LineShape {
         // cref: lineRender (pikchr.c:4271-4276) - larrow first, then rarrow
         // Render arrowheads before chopping endpoints
         if svg_points.len() >= 2 {
+            let n = svg_points.len();
             if self.style.arrow_start
-                && let Some(arrowhead) = render_arrowhead_dom(
+                && let Some(arrowhead) = draw_arrowhead(
                     svg_points[1],
-                    svg_points[0],
+                    &mut svg_points[0],
                     &self.style,
                     arrow_len_px,
                     arrow_wid_px,
                     ctx.use_css_vars,
                 )
             {
-                nodes.push(SvgNode::Polygon(arrowhead));
+                nodes.push(arrowhead);
             }
-            let n = svg_points.len();
             if self.style.arrow_end
-                && let Some(arrowhead) = render_arrowhead_dom(
+                && let Some(arrowhead) = draw_arrowhead(
                     svg_points[n - 2],
-                    svg_points[n - 1],
+                    &mut svg_points[n - 1],
                     &self.style,
                     arrow_len_px,
                     arrow_wid_px,
                     ctx.use_css_vars,
                 )
             {
-                nodes.push(SvgNode::Polygon(arrowhead));
-            }
-            // Chop endpoints for arrow space
-            if self.style.arrow_start {
-                let (new_start, _) = chop_line(svg_points[0], svg_points[1], arrow_chop);
-                svg_points[0] = new_start;
-            }
-            if self.style.arrow_end {
-                let (_, new_end) = chop_line(svg_points[n - 2], svg_points[n - 1], arrow_chop);
-                svg_points[n - 1] = new_end;
+                nodes.push(arrowhead);
             }
         }
 
@@ -1498,11 +1521,12 @@ impl Shape for LineShape {
         }
 
         let path = Path {
-            d: Some(path_data),
+            d: Some(fmt_path_data(&path_data)),
             fill: None,
             stroke: None,
             stroke_width: None,
             stroke_dasharray: None,
+            data_layer: None,
             style: Some(svg_style),
         };
         nodes.push(SvgNode::Path(path));
@@ -1613,7 +1637,7 @@ impl Shape for LineShape {
                     TextVSlot::Center => y_base,
                     TextVSlot::Below => y_base - hc * 0.5 - hb1 * 0.5,
                     TextVSlot::Below2 => y_base - hc * 0.5 - hb1 - hb2 * 0.5,
-                };
+                } + Inches(t.shift);
 
                 // Compute text bbox corners relative to center (as if horizontal)
                 // cref: pikchr.c:5175-5195
@@ -1657,12 +1681,10 @@ impl Shape for LineShape {
     /// Expand bounds WITHOUT arrowheads - used for computing sublist width/height
     /// cref: pikchr.y:1757-1761 - sublist bbox uses children's pObj->bbox (no arrowheads)
     /// cref: pikchr.y:4527 - pik_bbox_addbox adds pObj->bbox not arrowhead ellipses
+    /// cref: sublistInit (pikchr.c:4437-4444) - invisible children still count
     fn expand_core_bounds(&self, bounds: &mut BoundingBox) {
-        // Only expand by waypoints if visible (not invisible and stroke width non-negative)
-        if !self.style.invisible && self.style.stroke_width.0 >= 0.0 {
-            for pt in &self.waypoints {
-                bounds.expand_point(*pt);
-            }
+        for pt in &self.waypoints {
+            bounds.expand_point(*pt);
         }
         // NOTE: Arrowhead expansion is intentionally omitted here
         // It gets added during final SVG bbox computation via expand_bounds()
@@ -1790,47 +1812,44 @@ impl Shape for SplineShape {
         // cref: lineRender (pikchr.c:4271-4276) - larrow first, then rarrow
         // In C pikchr, pik_draw_arrowhead modifies aPath in place before radiusPath is called.
         // We need to shorten waypoints by half the arrow height for the path rendering.
+        // The path is built from waypoints in inches, so the chop draw_arrowhead applies
+        // in pixels is redone on the waypoints: h/2, where h = p->hArrow * pObj->sw, i.e.
+        // arrowht * arrow_scale, clamped to the segment like the head itself
+        let mut waypoints = self.waypoints.clone();
+        let arrow_len = ctx.arrow_len.raw() * arrow_scale;
+        let segment = |a: PointIn, b: PointIn| (b.x.0 - a.x.0).hypot(b.y.0 - a.y.0);
+
         if self.style.arrow_start && n >= 2 {
-            let p1 = self.waypoints[0].to_svg(ctx.scaler, ctx.offset_x, ctx.max_y);
-            let p2 = self.waypoints[1].to_svg(ctx.scaler, ctx.offset_x, ctx.max_y);
-            if let Some(arrowhead) = render_arrowhead_dom(
-                p2,
+            let mut p0 = waypoints[0].to_svg(ctx.scaler, ctx.offset_x, ctx.max_y);
+            let p1 = waypoints[1].to_svg(ctx.scaler, ctx.offset_x, ctx.max_y);
+            if let Some(arrowhead) = draw_arrowhead(
                 p1,
+                &mut p0,
                 &self.style,
                 arrow_len_px,
                 arrow_wid_px,
                 ctx.use_css_vars,
             ) {
-                nodes.push(SvgNode::Polygon(arrowhead));
+                nodes.push(arrowhead);
             }
+            let h = arrow_len.min(segment(waypoints[1], waypoints[0]));
+            chop_waypoint_start(&mut waypoints, Inches(h / 2.0));
         }
         if self.style.arrow_end && n >= 2 {
-            let p1 = self.waypoints[n - 2].to_svg(ctx.scaler, ctx.offset_x, ctx.max_y);
-            let p2 = self.waypoints[n - 1].to_svg(ctx.scaler, ctx.offset_x, ctx.max_y);
-            if let Some(arrowhead) = render_arrowhead_dom(
-                p1,
-                p2,
+            let p0 = waypoints[n - 2].to_svg(ctx.scaler, ctx.offset_x, ctx.max_y);
+            let mut p1 = waypoints[n - 1].to_svg(ctx.scaler, ctx.offset_x, ctx.max_y);
+            if let Some(arrowhead) = draw_arrowhead(
+                p0,
+                &mut p1,
                 &self.style,
                 arrow_len_px,
                 arrow_wid_px,
                 ctx.use_css_vars,
             ) {
-                nodes.push(SvgNode::Polygon(arrowhead));
+                nodes.push(arrowhead);
             }
-        }
-
-        // Clone waypoints and shorten endpoints where arrows exist
-        // cref: pik_chop shortens by h/2 where h = p->hArrow * pObj->sw
-        // Since hArrow = arrowht/thickness and we multiply by sw (stroke width),
-        // the chop amount is: (arrowht/thickness) * sw / 2 = arrowht * arrow_scale / 2
-        let mut waypoints = self.waypoints.clone();
-        let chop_amount = Inches(ctx.arrow_len.raw() * arrow_scale / 2.0);
-
-        if self.style.arrow_start && waypoints.len() >= 2 {
-            chop_waypoint_start(&mut waypoints, chop_amount);
-        }
-        if self.style.arrow_end && waypoints.len() >= 2 {
-            chop_waypoint_end(&mut waypoints, chop_amount);
+            let h = arrow_len.min(segment(waypoints[n - 2], waypoints[n - 1]));
+            chop_waypoint_end(&mut waypoints, Inches(h / 2.0));
         }
 
         // cref: splineRender (pikchr.c:1716-1718) - if n<3 or r<=0, use lineRender
@@ -1841,11 +1860,12 @@ impl Shape for SplineShape {
         };
 
         let path = Path {
-            d: Some(path_data),
+            d: Some(fmt_path_data(&path_data)),
             fill: None,
             stroke: None,
             stroke_width: None,
             stroke_dasharray: None,
+            data_layer: None,
             style: Some(svg_style),
         };
         nodes.push(SvgNode::Path(path));
@@ -1903,12 +1923,10 @@ impl Shape for SplineShape {
 
     /// Expand bounds WITHOUT arrowheads - used for computing sublist width/height
     /// cref: pikchr.y:1757-1761 - sublist bbox uses children's pObj->bbox (no arrowheads)
+    /// cref: sublistInit (pikchr.c:4437-4444) - invisible children still count
     fn expand_core_bounds(&self, bounds: &mut BoundingBox) {
-        // Only expand by waypoints if visible (not invisible and stroke width non-negative)
-        if !self.style.invisible && self.style.stroke_width.0 >= 0.0 {
-            for pt in &self.waypoints {
-                bounds.expand_point(*pt);
-            }
+        for pt in &self.waypoints {
+            bounds.expand_point(*pt);
         }
         // NOTE: Arrowhead expansion is intentionally omitted here
     }
@@ -1962,6 +1980,11 @@ impl Shape for DotShape {
         true
     }
 
+    // cref: dotOffset (pikchr.y:1370) - every compass point of a dot is its center
+    fn edge_point(&self, _direction: EdgeDirection) -> PointIn {
+        self.center
+    }
+
     // cref: dotCheck (pikchr.c:4042-4047)
     // C sets w = h = 0 for dots, so ptEnter = ptExit = ptAt (center)
     fn start(&self) -> PointIn {
@@ -2000,6 +2023,7 @@ impl Shape for DotShape {
             stroke: None,
             stroke_width: None,
             stroke_dasharray: None,
+            data_layer: None,
             style: Some(svg_style),
         };
         nodes.push(SvgNode::Circle(circle));
@@ -2113,7 +2137,7 @@ impl Shape for TextShape {
                     TextVSlot::Center => y_base,
                     TextVSlot::Below => y_base - hc * 0.5 - hb1 * 0.5,
                     TextVSlot::Below2 => y_base - hc * 0.5 - hb1 - hb2 * 0.5,
-                };
+                } + Inches(t.shift);
 
                 let line_y = center.y + y;
 
@@ -2152,21 +2176,55 @@ impl ArcShape {
             clockwise,
         }
     }
+
+    /// Bounding box of the curve without arrowheads or text
+    /// cref: arcCheck (pikchr.c:1040-1063) - samples 16 points along the quadratic bezier
+    /// cref: pik_after_adding_attributes (pikchr.y:4386-4388) - then adds the path vertices
+    fn curve_bounds(&self) -> BoundingBox {
+        let f = self.start;
+        let t = self.end;
+        // Calculate control point (in pikchr coordinates, Y-up)
+        let mid = f.midpoint(t);
+        let dx = t.x - f.x;
+        let dy = t.y - f.y;
+        let m = if self.clockwise {
+            Point::new(mid.x - dy * 0.5, mid.y + dx * 0.5)
+        } else {
+            Point::new(mid.x + dy * 0.5, mid.y - dx * 0.5)
+        };
+
+        let mut bounds = BoundingBox::new();
+        let sw = self.style.stroke_width;
+        for i in 1..16 {
+            let t1 = 0.0625 * i as f64;
+            let t2 = 1.0 - t1;
+            let a = t2 * t2;
+            let b = 2.0 * t1 * t2;
+            let c = t1 * t1;
+            let x = Inches(a * f.x.0 + b * m.x.0 + c * t.x.0);
+            let y = Inches(a * f.y.0 + b * m.y.0 + c * t.y.0);
+            // cref: pik_bbox_addellipse - expand by stroke width
+            bounds.expand_point(Point::new(x - sw, y - sw));
+            bounds.expand_point(Point::new(x + sw, y + sw));
+        }
+        bounds.expand_point(f);
+        bounds.expand_point(t);
+        bounds
+    }
 }
 
 impl Shape for ArcShape {
+    /// cref: pik_after_adding_attributes (pikchr.y:4389) - a line's center is its bbox center
     fn center(&self) -> PointIn {
-        self.start.midpoint(self.end)
+        self.curve_bounds().center()
     }
 
     fn width(&self) -> Inches {
-        let delta = self.end - self.start;
-        delta.dx.abs()
+        self.curve_bounds().width()
     }
 
     fn height(&self) -> Inches {
-        let delta = self.end - self.start;
-        delta.dy.abs()
+        self.curve_bounds().height()
     }
 
     fn style(&self) -> &ObjectStyle {
@@ -2210,37 +2268,32 @@ impl Shape for ArcShape {
         };
         let arrow_len_px = ctx.scaler.px(ctx.arrow_len) * arrow_scale;
         let arrow_wid_px = ctx.scaler.px(ctx.arrow_wid) * arrow_scale;
-        let arrow_chop = arrow_len_px / 2.0;
 
         // cref: arcRender (pikchr.c:1071-1076) - render arrowheads first, which modifies endpoints
         // pik_draw_arrowhead calls pik_chop to shorten the endpoint by h/2
-        if self.style.arrow_start {
-            if let Some(arrowhead) = render_arrowhead_dom(
+        if self.style.arrow_start
+            && let Some(arrowhead) = draw_arrowhead(
                 control,
-                start_svg,
+                &mut start_svg,
                 &self.style,
                 arrow_len_px,
                 arrow_wid_px,
                 ctx.use_css_vars,
-            ) {
-                nodes.push(SvgNode::Polygon(arrowhead));
-            }
-            // Chop start point: shorten from control toward start by arrow_chop
-            start_svg = chop_point(control, start_svg, arrow_chop);
+            )
+        {
+            nodes.push(arrowhead);
         }
-        if self.style.arrow_end {
-            if let Some(arrowhead) = render_arrowhead_dom(
+        if self.style.arrow_end
+            && let Some(arrowhead) = draw_arrowhead(
                 control,
-                end_svg,
+                &mut end_svg,
                 &self.style,
                 arrow_len_px,
                 arrow_wid_px,
                 ctx.use_css_vars,
-            ) {
-                nodes.push(SvgNode::Polygon(arrowhead));
-            }
-            // Chop end point: shorten from control toward end by arrow_chop
-            end_svg = chop_point(control, end_svg, arrow_chop);
+            )
+        {
+            nodes.push(arrowhead);
         }
 
         // cref: arcRender (pikchr.c:1077-1079) - render the arc path with chopped endpoints
@@ -2249,11 +2302,12 @@ impl Shape for ArcShape {
         let arc_path_data = create_arc_path_with_control(start_svg, control, end_svg);
 
         let arc_path = Path {
-            d: Some(arc_path_data),
+            d: Some(fmt_path_data(&arc_path_data)),
             fill: None,
             stroke: None,
             stroke_width: None,
             stroke_dasharray: None,
+            data_layer: None,
             style: Some(svg_style),
         };
         nodes.push(SvgNode::Path(arc_path));
@@ -2266,42 +2320,19 @@ impl Shape for ArcShape {
         self.end += offset;
     }
 
-    /// cref: arcCheck (pikchr.c:1040-1063) - arc bbox samples 16 points along the curve
     fn expand_bounds(&self, bounds: &mut BoundingBox) {
         // Skip bounds expansion for invisible arcs
         if self.style.invisible {
             return;
         }
 
-        // cref: arcCheck (pikchr.c:1048-1062) - sample 16 points along the quadratic bezier
-        let f = self.start;
-        let t = self.end;
-        // Calculate control point (in pikchr coordinates, Y-up)
-        let mid = f.midpoint(t);
-        let dx = t.x - f.x;
-        let dy = t.y - f.y;
-        let m = if self.clockwise {
-            Point::new(mid.x - dy * 0.5, mid.y + dx * 0.5)
-        } else {
-            Point::new(mid.x + dy * 0.5, mid.y - dx * 0.5)
-        };
-
-        let sw = self.style.stroke_width;
-        for i in 1..16 {
-            let t1 = 0.0625 * i as f64;
-            let t2 = 1.0 - t1;
-            let a = t2 * t2;
-            let b = 2.0 * t1 * t2;
-            let c = t1 * t1;
-            let x = Inches(a * f.x.0 + b * m.x.0 + c * t.x.0);
-            let y = Inches(a * f.y.0 + b * m.y.0 + c * t.y.0);
-            // cref: pik_bbox_addellipse - expand by stroke width
-            bounds.expand_point(Point::new(x - sw, y - sw));
-            bounds.expand_point(Point::new(x + sw, y + sw));
-        }
+        let core = self.curve_bounds();
+        bounds.expand_point(core.min);
+        bounds.expand_point(core.max);
 
         // cref: pik_bbox_add_elist (pikchr.c:4532-4542) - add arrowhead bounds at endpoints
         // wArrow = 0.5 * arrowwid (default arrowwid = 0.05")
+        let (f, t) = (self.start, self.end);
         let w_arrow = defaults::ARROW_WID * 0.5;
         if self.style.arrow_start {
             bounds.expand_point(Point::new(f.x - w_arrow, f.y - w_arrow));
@@ -2324,36 +2355,11 @@ impl Shape for ArcShape {
 
     /// Expand bounds WITHOUT arrowheads - used for computing sublist width/height
     /// cref: pikchr.y:1757-1761 - sublist bbox uses children's pObj->bbox (no arrowheads)
+    /// cref: sublistInit (pikchr.c:4437-4444) - invisible children still count
     fn expand_core_bounds(&self, bounds: &mut BoundingBox) {
-        // Skip bounds expansion for invisible arcs
-        if self.style.invisible {
-            return;
-        }
-
-        // Sample 16 points along the quadratic bezier (same as expand_bounds)
-        let f = self.start;
-        let t = self.end;
-        let mid = f.midpoint(t);
-        let dx = t.x - f.x;
-        let dy = t.y - f.y;
-        let m = if self.clockwise {
-            Point::new(mid.x - dy * 0.5, mid.y + dx * 0.5)
-        } else {
-            Point::new(mid.x + dy * 0.5, mid.y - dx * 0.5)
-        };
-
-        let sw = self.style.stroke_width;
-        for i in 1..16 {
-            let t1 = 0.0625 * i as f64;
-            let t2 = 1.0 - t1;
-            let a = t2 * t2;
-            let b = 2.0 * t1 * t2;
-            let c = t1 * t1;
-            let x = Inches(a * f.x.0 + b * m.x.0 + c * t.x.0);
-            let y = Inches(a * f.y.0 + b * m.y.0 + c * t.y.0);
-            bounds.expand_point(Point::new(x - sw, y - sw));
-            bounds.expand_point(Point::new(x + sw, y + sw));
-        }
+        let core = self.curve_bounds();
+        bounds.expand_point(core.min);
+        bounds.expand_point(core.max);
         // NOTE: Arrowhead expansion is intentionally omitted here
     }
 }
@@ -2418,6 +2424,31 @@ impl Shape for MoveShape {
         self.start += offset;
         self.end += offset;
     }
+
+    /// cref: moveInit (pikchr.c:4298) - moves have sw = -1, so only their text is added
+    /// cref: pik_bbox_add_elist (pikchr.y:4528) - the text box is added even though
+    /// moveRender draws nothing, so labels still take up room
+    fn expand_bounds(&self, bounds: &mut BoundingBox) {
+        if !self.text.is_empty() {
+            let charht = defaults::FONT_SIZE;
+            let (text_above, text_below) = sum_text_heights_above_below(&self.text, charht);
+            let center = self.center();
+            let top = center.y + Inches(text_above);
+            let bottom = center.y - Inches(text_below);
+            for t in &self.text {
+                let w = Inches(t.width_inches(defaults::CHARWID));
+                let (left, right) = if t.ljust {
+                    (center.x, center.x + w)
+                } else if t.rjust {
+                    (center.x - w, center.x)
+                } else {
+                    (center.x - w / 2.0, center.x + w / 2.0)
+                };
+                bounds.expand_point(Point::new(left, bottom));
+                bounds.expand_point(Point::new(right, top));
+            }
+        }
+    }
 }
 
 /// A sublist shape - container for child shapes
@@ -2485,7 +2516,18 @@ impl Shape for SublistShape {
     }
 
     /// cref: pik_bbox_add_elist (pikchr.c:7206) - sublist bbox from children (with arrowheads)
+    /// cref: pik_bbox_add_elist (pikchr.c:7252) - the sublist's own bbox covers invisible
+    /// children, so spacers inside `[...]` keep their extent
     fn expand_bounds(&self, bounds: &mut BoundingBox) {
+        if !self.style.invisible && self.style.stroke_width.0 >= 0.0 {
+            bounds.expand_rect(
+                self.center,
+                Size {
+                    w: self.width,
+                    h: self.height,
+                },
+            );
+        }
         for child in &self.children {
             let shape = &child.shape;
             shape.expand_bounds(bounds);
@@ -2700,21 +2742,24 @@ fn build_svg_style_full(
     let mut entries = vec![
         ("fill", fill_rgb),
         ("stroke", stroke_rgb),
-        ("stroke-width", format!("{}", scaler.px(style.stroke_width))),
+        ("stroke-width", fmt_num(scaler.px(style.stroke_width))),
     ];
 
     // Dashed: dash and gap are both the stored width
-    // cref: pik_append_style
+    // cref: pik_append_style (pikchr.y:2292-2296)
     if let Some(dash_width) = style.dashed {
-        let dash = scaler.px(dash_width);
+        let dash = fmt_num(scaler.px(dash_width));
         entries.push(("stroke-dasharray", format!("{},{}", dash, dash)));
     }
     // Dotted: dot is stroke width, gap is the stored width
-    // cref: pik_append_style
+    // cref: pik_append_style (pikchr.y:2287-2291) - dots are never shorter than 2.1px
     else if let Some(gap_width) = style.dotted {
-        let dot = scaler.px(style.stroke_width);
+        let dot = scaler.px(style.stroke_width).max(2.1);
         let gap = scaler.px(gap_width);
-        entries.push(("stroke-dasharray", format!("{},{}", dot, gap)));
+        entries.push((
+            "stroke-dasharray",
+            format!("{},{}", fmt_num(dot), fmt_num(gap)),
+        ));
     }
 
     // Add stroke-linejoin:round for closed paths with sharp corners
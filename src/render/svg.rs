@@ -1,11 +1,12 @@
 //! SVG generation
 
+use super::dom::{Circle as SvgCircle, Group, Path, Polygon, Style, Svg, SvgNode, Text};
 use super::shapes::{Shape, ShapeRenderContext, svg_style_from_entries};
 use super::{TextVSlot, compute_text_vslots};
 use crate::errors::PikruError;
 use crate::types::{Length as Inches, Scaler};
-use facet_svg::facet_xml::SerializeOptions;
-use facet_svg::{Circle as SvgCircle, Points, Polygon, Style, Svg, SvgNode, Text, facet_xml};
+use facet_svg::facet_xml::{self, SerializeOptions};
+use facet_svg::{PathData, Points};
 use glam::{DVec2, dvec2};
 
 use super::context::RenderContext;
@@ -119,6 +120,9 @@ fn process_backslash_escapes(s: &str) -> String {
 /// Since facet-xml with preserve_entities:true will pass through entity-like sequences,
 /// we just need to escape bare `&` that aren't part of entities, plus `<` and `>`.
 ///
+/// Unlike C, named entities XML does not define are replaced by their character
+/// (see [`super::entities`]), or escaped if unknown, so the SVG stays well-formed.
+///
 /// cref: pik_isentity (pikchr.c:2043) - checks if text starts with HTML entity
 /// cref: pik_append_text (pikchr.c:2066) - handles entity pass-through
 fn process_entities_for_svg(s: &str) -> String {
@@ -140,9 +144,22 @@ fn process_entities_for_svg(s: &str) -> String {
             b'&' => {
                 // Check if this looks like an entity: &[#]?[a-zA-Z0-9]+;
                 if is_entity_at(bytes, i) {
-                    // Pass through the entity unchanged
-                    result.push('&');
-                    i += 1;
+                    let end = i + s[i..].find(';').unwrap_or(0);
+                    let name = &s[i + 1..end];
+                    let is_xml = name.starts_with('#')
+                        || matches!(name, "amp" | "lt" | "gt" | "quot" | "apos");
+                    if is_xml {
+                        // Pass through the entity unchanged
+                        result.push('&');
+                        i += 1;
+                    } else if let Some(c) = super::entities::html_entity(name) {
+                        result.push(c);
+                        i = end + 1;
+                    } else {
+                        // Unknown to both XML and HTML: keep it as literal text
+                        result.push_str("&amp;");
+                        i += 1;
+                    }
                 } else {
                     // Bare & - escape it
                     result.push_str("&amp;");
@@ -250,6 +267,100 @@ fn generate_color_css() -> Style {
     }
 }
 
+/// Generate the keyframes used by `animate_reveal`
+fn generate_reveal_css() -> Style {
+    Style {
+        type_: Some("text/css".to_string()),
+        content: Some(
+            "@keyframes pik-reveal {\n  from { opacity: 0; }\n  to { opacity: 1; }\n}\n"
+                .to_string(),
+        ),
+    }
+}
+
+/// Generate the `@font-face` rule used by `embed_font`. Monospace text keeps its
+/// own `font-family` attribute, so only text without one picks up the font.
+fn generate_font_css(font: &[u8]) -> Style {
+    use base64::Engine;
+
+    let family = defaults::EMBEDDED_FONT_FAMILY;
+    let data = base64::engine::general_purpose::STANDARD.encode(font);
+    let src = match font_format(font) {
+        Some((mime, format)) => format!("url(data:{mime};base64,{data}) format(\"{format}\")"),
+        None => format!("url(data:application/octet-stream;base64,{data})"),
+    };
+    Style {
+        type_: Some("text/css".to_string()),
+        content: Some(format!(
+            "@font-face {{\n  font-family: {family};\n  src: {src};\n}}\ntext:not([font-family]) {{ font-family: {family}; }}\n"
+        )),
+    }
+}
+
+/// MIME type and CSS `format()` hint for a font, from its magic bytes.
+/// `None` for anything else, which leaves the browser to sniff it.
+fn font_format(font: &[u8]) -> Option<(&'static str, &'static str)> {
+    match font.get(..4)? {
+        b"wOFF" => Some(("font/woff", "woff")),
+        b"wOF2" => Some(("font/woff2", "woff2")),
+        b"\0\x01\0\0" | b"true" => Some(("font/ttf", "truetype")),
+        b"OTTO" => Some(("font/otf", "opentype")),
+        _ => None,
+    }
+}
+
+/// Append the `animate_reveal` animation to the inline style of each node
+fn apply_reveal_delay(nodes: &mut [SvgNode], delay: f64) {
+    let decl = format!(
+        "animation:pik-reveal {}s ease-in both;animation-delay:{}s;",
+        fmt_num(defaults::REVEAL_DURATION),
+        fmt_num(delay)
+    );
+    append_style(nodes, &decl);
+}
+
+/// Append CSS declarations to the inline style of each node
+fn append_style(nodes: &mut [SvgNode], decl: &str) {
+    for node in nodes {
+        let style = match node {
+            SvgNode::Path(n) => &mut n.style,
+            SvgNode::Polygon(n) => &mut n.style,
+            SvgNode::Circle(n) => &mut n.style,
+            SvgNode::Ellipse(n) => &mut n.style,
+            SvgNode::Text(n) => &mut n.style,
+            SvgNode::G(g) => {
+                append_style(&mut g.children, decl);
+                continue;
+            }
+            _ => continue,
+        };
+        let style = style.get_or_insert_with(String::new);
+        if !style.is_empty() && !style.ends_with(';') {
+            style.push(';');
+        }
+        style.push_str(decl);
+    }
+}
+
+/// Set the `data-layer` attribute of each node, for `emit_layers`
+fn set_data_layer(nodes: &mut [SvgNode], layer: i32) {
+    for node in nodes {
+        let data_layer = match node {
+            SvgNode::Path(n) => &mut n.data_layer,
+            SvgNode::Polygon(n) => &mut n.data_layer,
+            SvgNode::Circle(n) => &mut n.data_layer,
+            SvgNode::Ellipse(n) => &mut n.data_layer,
+            SvgNode::Text(n) => &mut n.data_layer,
+            SvgNode::G(g) => {
+                set_data_layer(&mut g.children, layer);
+                continue;
+            }
+            SvgNode::Style(_) => continue,
+        };
+        *data_layer = Some(layer.to_string());
+    }
+}
+
 /// Generate SVG from render context
 // cref: pik_render (pikchr.c:7253) - main SVG output function
 pub fn generate_svg(
@@ -297,11 +408,15 @@ pub fn generate_svg(
     let min_dim = Inches(0.01);
     let view_width = bounds.width().max(min_dim);
     let view_height = bounds.height().max(min_dim);
-    let offset_x = -bounds.min.x;
     // Y-flip: C pikchr uses `y = bbox.ne.y - y` to flip from Y-up to SVG Y-down
     // For to_svg(), we pass max_y = bounds.max.y so that:
     //   svg_y = scaler.px(max_y - point.y) which gives the correct flipped coordinate
-    let max_y = bounds.max.y;
+    // Without translate_origin only the flip is applied and the viewBox moves instead.
+    let (offset_x, max_y) = if options.translate_origin {
+        (-bounds.min.x, bounds.max.y)
+    } else {
+        (Inches(0.0), Inches(0.0))
+    };
 
     crate::log::debug!(
         bounds_min_x = bounds.min.x.0,
@@ -315,22 +430,67 @@ pub fn generate_svg(
 
     // Build SVG DOM
     let mut svg_children: Vec<SvgNode> = Vec::new();
+    let viewbox_width = scaler.px(view_width);
+    let viewbox_height = scaler.px(view_height);
+    let (viewbox_x, viewbox_y) = if options.translate_origin {
+        (0.0, 0.0)
+    } else {
+        (scaler.px(bounds.min.x), -scaler.px(bounds.max.y))
+    };
+
+    // Background first, so everything else paints over it
+    if let Some(background) = &options.background {
+        let (x1, y1) = (viewbox_x, viewbox_y);
+        let (x2, y2) = (x1 + viewbox_width, y1 + viewbox_height);
+        let fill = color_to_string(&background.to_rgb_string(), options.css_variables);
+        svg_children.push(SvgNode::Path(Path {
+            d: Some(fmt_path_data(
+                &PathData::new().m(x1, y1).l(x2, y1).l(x2, y2).l(x1, y2).z(),
+            )),
+            fill: None,
+            stroke: None,
+            stroke_width: None,
+            stroke_dasharray: None,
+            data_layer: None,
+            style: Some(svg_style_from_entries(vec![
+                ("fill", fill),
+                ("stroke", "none".to_string()),
+            ])),
+        }));
+    }
 
     // Add CSS variables style block if enabled
     if options.css_variables {
         svg_children.push(SvgNode::Style(generate_color_css()));
     }
+    if options.animate_reveal {
+        svg_children.push(SvgNode::Style(generate_reveal_css()));
+    }
+    if let Some(font) = &options.embed_font {
+        svg_children.push(SvgNode::Style(generate_font_css(font)));
+    }
 
     // SVG header - C pikchr only adds width/height when scale != 1.0
-    let viewbox_width = scaler.px(view_width);
-    let viewbox_height = scaler.px(view_height);
-
-    // Create the main SVG element
-    let viewbox = format!("0 0 {} {}", fmt_num(viewbox_width), fmt_num(viewbox_height));
+    // Create the main SVG element.
+    // No `xmlns:xlink` is declared: pikru never emits `<use>`, gradients, patterns or any
+    // other `href` reference (unsupported fills are rejected during evaluation), so there
+    // is nothing to resolve; debug builds check this with `find_dangling_reference`. If
+    // such references are ever added, `dom::Svg` needs an xlink namespace attribute
+    // first.
+    let viewbox = format!(
+        "{} {} {} {}",
+        fmt_num(viewbox_x),
+        fmt_num(viewbox_y),
+        fmt_num(viewbox_width),
+        fmt_num(viewbox_height)
+    );
+    // cref: pik_render (pikchr.y:4640) - the check-in date of the tracked C release
+    let date = options.pikchr_date && options.compat.has_date_attribute();
     let mut svg = Svg {
         width: None,
         height: None,
         view_box: Some(viewbox),
+        data_pikchr_date: date.then(|| defaults::PIKCHR_DATE.to_string()),
         children: Vec::new(),
     };
 
@@ -373,6 +533,11 @@ pub fn generate_svg(
         use_css_vars: bool,
         svg_children: &mut Vec<SvgNode>,
     ) {
+        // cref: moveRender (pikchr.y:1623) - a no-op, labels included
+        if obj.class() == ClassName::Move {
+            return;
+        }
+
         // Convert from pikchr coordinates (Y-up) to SVG pixels (Y-down)
         let center = obj.center().to_svg(scaler, offset_x, max_y);
 
@@ -443,6 +608,8 @@ pub fn generate_svg(
                     TextVSlot::Below => y_offset -= 0.5 * hc + 0.5 * hb1,
                     TextVSlot::Below2 => y_offset -= 0.5 * hc + hb1 + 0.5 * hb2,
                 }
+                // Extra distance from `above 0.2` / `below 0.2` (pikru extension)
+                y_offset += positioned_text.shift;
 
                 let svg_y_offset = scaler.px(Inches::inches(-y_offset));
 
@@ -534,6 +701,7 @@ pub fn generate_svg(
                     fill: Some(text_color.clone()),
                     stroke: None,
                     stroke_width: None,
+                    data_layer: None,
                     style: None,
                     font_family,
                     font_style,
@@ -542,7 +710,9 @@ pub fn generate_svg(
                     text_anchor: Some(anchor.to_string()),
                     dominant_baseline: Some("central".to_string()),
                     content: Some({
-                        // Process in order: backslash escapes first, then entities for SVG, then spaces
+                        // Process in order: backslash escapes first, then entities for SVG, then spaces.
+                        // No-break spaces are never collapsed, so runs of spaces and leading or
+                        // trailing ones survive without `xml:space="preserve"` (deprecated in SVG 2)
                         let text = process_backslash_escapes(&positioned_text.value);
                         let text = process_entities_for_svg(&text);
                         text.replace(' ', "\u{00A0}")
@@ -571,10 +741,12 @@ pub fn generate_svg(
         }
     }
 
-    // Sort objects by layer for rendering (lower layers first = behind)
-    // cref: pik_render (pikchr.c:5619) - renders by layer
-    let mut sorted_objects: Vec<_> = ctx.object_list.iter().collect();
-    sorted_objects.sort_by_key(|obj| obj.layer);
+    // Sort objects by layer for rendering (lower layers first = behind).
+    // sort_by_key is stable, so objects on the same layer keep source order.
+    // cref: pik_elist_render (pikchr.c:7194-7216) - one pass per layer, in list order
+    // Keep each object's placement index for animate_reveal, which follows placement order.
+    let mut sorted_objects: Vec<_> = ctx.object_list.iter().enumerate().collect();
+    sorted_objects.sort_by_key(|(_, obj)| obj.layer);
 
     // Helper to render a single object (shape + text), recursing into sublist children
     // This ensures text is rendered inline with each shape, matching C pikchr order
@@ -593,6 +765,7 @@ pub fn generate_svg(
         thickness: f64,
         fontscale: f64,
         use_css_vars: bool,
+        group_arrows: bool,
         svg_children: &mut Vec<SvgNode>,
     ) {
         // For sublists, render each child (shape + text) sorted by layer
@@ -615,11 +788,13 @@ pub fn generate_svg(
                     thickness,
                     fontscale,
                     use_css_vars,
+                    group_arrows,
                     svg_children,
                 );
             }
         } else {
             // Non-sublist: render shape then text immediately after
+            let first_node = svg_children.len();
             if !obj.style().invisible {
                 let shape = &obj.shape;
                 let ctx = ShapeRenderContext {
@@ -648,6 +823,15 @@ pub fn generate_svg(
                 use_css_vars,
                 svg_children,
             );
+            // One group per arrow, holding its path, heads and label, for CSS to target
+            let has_heads = obj.style().arrow_start || obj.style().arrow_end;
+            if group_arrows && has_heads && svg_children.len() > first_node {
+                let children = svg_children.split_off(first_node);
+                svg_children.push(SvgNode::G(Group {
+                    class: Some("arrow".to_string()),
+                    children,
+                }));
+            }
         }
     }
 
@@ -655,7 +839,8 @@ pub fn generate_svg(
     // cref: pikchr.c:7289-7290 - charht and charwid are scaled by fontscale
     let charht = get_length(ctx, "charht", 0.14) * fontscale;
     let charwid = get_length(ctx, "charwid", 0.08) * fontscale;
-    for obj in sorted_objects.iter() {
+    for (placement, obj) in sorted_objects.iter() {
+        let first_node = svg_children.len();
         render_object_full(
             obj,
             &scaler,
@@ -669,8 +854,16 @@ pub fn generate_svg(
             thickness,
             fontscale,
             options.css_variables,
+            options.group_arrows,
             &mut svg_children,
         );
+        if options.animate_reveal {
+            let delay = *placement as f64 * defaults::REVEAL_STEP;
+            apply_reveal_delay(&mut svg_children[first_node..], delay);
+        }
+        if options.emit_layers {
+            set_data_layer(&mut svg_children[first_node..], obj.layer);
+        }
     }
 
     // cref: pik_elist_render (pikchr.c:4497-4518) - render debug labels if debug_label_color is set
@@ -698,6 +891,7 @@ pub fn generate_svg(
                 stroke: Some(color_str.clone()),
                 stroke_width: Some(sw_px.clone()),
                 stroke_dasharray: None,
+                data_layer: None,
                 style: None,
             };
             svg_children.push(SvgNode::Circle(circle));
@@ -712,6 +906,7 @@ pub fn generate_svg(
                 fill: Some(color_str.clone()),
                 stroke: None,
                 stroke_width: None,
+                data_layer: None,
                 style: None,
                 font_family: None,
                 font_style: None,
@@ -725,7 +920,7 @@ pub fn generate_svg(
         };
 
         // Render debug labels for objects with explicit names
-        for obj in sorted_objects.iter() {
+        for (_, obj) in sorted_objects.iter() {
             if let Some(ref name) = obj.name
                 && obj.name_is_explicit
             {
@@ -735,6 +930,7 @@ pub fn generate_svg(
         }
 
         // Render debug labels for named positions (e.g., `OUT: 6.3in right of previous.e`)
+        // These follow definition order, like C's element list
         for (name, pos) in ctx.named_positions.iter() {
             let center = pos.to_svg(&scaler, offset_x, max_y);
             render_debug_label(name, center);
@@ -753,10 +949,152 @@ pub fn generate_svg(
     let options_ser = SerializeOptions {
         float_formatter: Some(format_float),
         preserve_entities: true,
+        pretty: options.pretty,
         ..Default::default()
     };
-    facet_xml::to_string_with_options(&svg, &options_ser)
-        .map_err(|e| PikruError::Generic(format!("XML serialization error: {}", e)))
+    let output = facet_xml::to_string_with_options(&svg, &options_ser)
+        .map_err(|e| PikruError::Generic(format!("XML serialization error: {}", e)))?;
+
+    // Every `url(#id)` / `href="#id"` must point at an element we emitted
+    if cfg!(debug_assertions)
+        && let Some(id) = find_dangling_reference(&output)
+    {
+        panic!("SVG references undefined id #{}", id);
+    }
+
+    Ok(output)
+}
+
+/// Serialize path data like facet_svg does, but with numbers formatted like C
+pub(crate) fn fmt_path_data(data: &PathData) -> String {
+    use facet_svg::PathCommand::*;
+
+    let flag = |b: bool| if b { "1" } else { "0" };
+    let mut result = String::new();
+    for cmd in &data.commands {
+        let (letter, args): (char, Vec<String>) = match *cmd {
+            MoveTo { x, y } => ('M', vec![fmt_pair(x, y)]),
+            MoveToRel { dx, dy } => ('m', vec![fmt_pair(dx, dy)]),
+            LineTo { x, y } => ('L', vec![fmt_pair(x, y)]),
+            LineToRel { dx, dy } => ('l', vec![fmt_pair(dx, dy)]),
+            HorizontalLineTo { x } => ('H', vec![fmt_num(x)]),
+            HorizontalLineToRel { dx } => ('h', vec![fmt_num(dx)]),
+            VerticalLineTo { y } => ('V', vec![fmt_num(y)]),
+            VerticalLineToRel { dy } => ('v', vec![fmt_num(dy)]),
+            CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => (
+                'C',
+                vec![fmt_pair(x1, y1), fmt_pair(x2, y2), fmt_pair(x, y)],
+            ),
+            CurveToRel {
+                dx1,
+                dy1,
+                dx2,
+                dy2,
+                dx,
+                dy,
+            } => (
+                'c',
+                vec![fmt_pair(dx1, dy1), fmt_pair(dx2, dy2), fmt_pair(dx, dy)],
+            ),
+            SmoothCurveTo { x2, y2, x, y } => ('S', vec![fmt_pair(x2, y2), fmt_pair(x, y)]),
+            SmoothCurveToRel { dx2, dy2, dx, dy } => {
+                ('s', vec![fmt_pair(dx2, dy2), fmt_pair(dx, dy)])
+            }
+            QuadTo { x1, y1, x, y } => ('Q', vec![fmt_pair(x1, y1), fmt_pair(x, y)]),
+            QuadToRel { dx1, dy1, dx, dy } => ('q', vec![fmt_pair(dx1, dy1), fmt_pair(dx, dy)]),
+            SmoothQuadTo { x, y } => ('T', vec![fmt_pair(x, y)]),
+            SmoothQuadToRel { dx, dy } => ('t', vec![fmt_pair(dx, dy)]),
+            Arc {
+                rx,
+                ry,
+                x_rotation,
+                large_arc,
+                sweep,
+                x,
+                y,
+            } => (
+                'A',
+                vec![
+                    fmt_pair(rx, ry),
+                    fmt_num(x_rotation),
+                    flag(large_arc).to_string(),
+                    flag(sweep).to_string(),
+                    fmt_pair(x, y),
+                ],
+            ),
+            ArcRel {
+                rx,
+                ry,
+                x_rotation,
+                large_arc,
+                sweep,
+                dx,
+                dy,
+            } => (
+                'a',
+                vec![
+                    fmt_pair(rx, ry),
+                    fmt_num(x_rotation),
+                    flag(large_arc).to_string(),
+                    flag(sweep).to_string(),
+                    fmt_pair(dx, dy),
+                ],
+            ),
+            ClosePath => ('Z', Vec::new()),
+        };
+        result.push(letter);
+        result.push_str(&args.join(" "));
+    }
+    result
+}
+
+/// Serialize polygon points like facet_svg does, but with numbers formatted like C
+pub(crate) fn fmt_points(points: &Points) -> String {
+    points
+        .points
+        .iter()
+        .map(|p| fmt_pair(p.x, p.y))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn fmt_pair(x: f64, y: f64) -> String {
+    format!("{},{}", fmt_num(x), fmt_num(y))
+}
+
+/// Find the first `url(#id)` or `href="#id"` reference in the markup whose target id
+/// is not defined by any element. Only tag markup is scanned, so label text that
+/// happens to look like a reference is ignored.
+pub(crate) fn find_dangling_reference(svg: &str) -> Option<String> {
+    fn values<'a>(tag: &'a str, prefix: &str, end: char) -> impl Iterator<Item = &'a str> {
+        tag.match_indices(prefix).filter_map(move |(i, _)| {
+            let rest = &tag[i + prefix.len()..];
+            rest.find(end).map(|j| &rest[..j])
+        })
+    }
+
+    let tags: Vec<&str> = svg
+        .split('<')
+        .skip(1)
+        .filter_map(|chunk| chunk.split_once('>').map(|(tag, _)| tag))
+        .collect();
+
+    let mut ids = std::collections::HashSet::new();
+    for tag in &tags {
+        ids.extend(values(tag, " id=\"", '"'));
+    }
+
+    tags.iter()
+        .flat_map(|tag| values(tag, "url(#", ')').chain(values(tag, "href=\"#", '"')))
+        .find(|id| !ids.contains(id))
+        .map(str::to_string)
 }
 
 /// Render an arrowhead polygon at the end of a line
@@ -800,11 +1138,12 @@ pub fn render_arrowhead_dom(
     let fill_color = color_to_string(&style.stroke, use_css_vars);
 
     Some(Polygon {
-        points,
+        points: Some(fmt_points(&points)),
         fill: None,
         stroke: None,
         stroke_width: None,
         stroke_dasharray: None,
+        data_layer: None,
         style: Some(svg_style_from_entries(vec![("fill", fill_color)])),
     })
 }
@@ -821,22 +1160,35 @@ pub(crate) fn fmt_num_hi(value: f64) -> String {
     fmt_num_precision(value, 10)
 }
 
-/// Format a number with specified significant figures, trailing zeros trimmed.
+/// Format a number with specified significant figures like C's %.Ng: trailing zeros
+/// are trimmed, and exponent notation is used when the exponent is below -4 or at
+/// least the precision (e.g. `1.44e-05`, `1.44e+06`).
 fn fmt_num_precision(value: f64, sig_figs: i32) -> String {
     if value == 0.0 {
         return "0".to_string();
     }
 
-    // Round to specified significant figures
-    let abs_val = value.abs();
-    let magnitude = abs_val.log10().floor() as i32;
-    let scale = 10_f64.powi(sig_figs - 1 - magnitude);
-    let rounded = (value * scale).round() / scale;
+    // Round to the requested significant figures first; the exponent %g decides on
+    // is the one after rounding (999999.7 becomes 1e+06)
+    let sci = format!("{:.prec$e}", value, prec = (sig_figs - 1) as usize);
+    let (mantissa, exponent) = sci.split_once('e').expect("{:e} always has an exponent");
+    let exponent: i32 = exponent.parse().expect("{:e} exponent is an integer");
+
+    if exponent < -4 || exponent >= sig_figs {
+        let mantissa = trim_fraction_zeros(mantissa);
+        let sign = if exponent < 0 { '-' } else { '+' };
+        return format!("{}e{}{:02}", mantissa, sign, exponent.abs());
+    }
+
+    let decimals = (sig_figs - 1 - exponent) as usize;
+    trim_fraction_zeros(&format!("{:.prec$}", value, prec = decimals)).to_string()
+}
 
-    // Format with enough decimal places, then trim
-    let decimals = (sig_figs - 1 - magnitude).max(0) as usize;
-    let s = format!("{:.prec$}", rounded, prec = decimals);
-    let s = s.trim_end_matches('0');
-    let s = s.trim_end_matches('.');
-    s.to_string()
+/// Trim trailing zeros (and a dangling decimal point) from a formatted number.
+fn trim_fraction_zeros(s: &str) -> &str {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        s
+    }
 }
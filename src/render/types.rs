@@ -4,6 +4,7 @@ use crate::ast::TextAttr;
 use crate::errors::PikruError;
 use crate::types::{BoxIn, EvalValue, Length as Inches, OffsetIn, Point, PtIn, UnitVec};
 
+use super::context::RenderContext;
 use super::defaults;
 use super::shapes::Shape;
 
@@ -89,6 +90,7 @@ pub struct PositionedText {
     pub small: bool,
     pub xtra: bool,    // Amplify big or small (for double big/small)
     pub aligned: bool, // Rotate text to align with line direction
+    pub shift: f64,    // Extra vertical offset in inches from `above 0.2` / `below 0.2`
 }
 
 impl PositionedText {
@@ -107,18 +109,33 @@ impl PositionedText {
             small: false,
             xtra: false,
             aligned: false,
+            shift: 0.0,
         }
     }
 
-    pub fn from_textposition(value: String, pos: Option<&crate::ast::TextPosition>) -> Self {
+    pub fn from_textposition(
+        ctx: &RenderContext,
+        value: String,
+        pos: Option<&crate::ast::TextPosition>,
+    ) -> Result<Self, PikruError> {
         let mut pt = Self::new(value);
         if let Some(pos) = pos {
             // cref: pik_txt_token (pikchr.c:6262-6265)
             // If we see a second Big or Small, set xtra flag
             for attr in &pos.attrs {
                 match attr {
-                    TextAttr::Above => pt.above = true,
-                    TextAttr::Below => pt.below = true,
+                    TextAttr::Above(distance) => {
+                        pt.above = true;
+                        if let Some(distance) = distance {
+                            pt.shift += super::eval::eval_len(ctx, distance)?.raw();
+                        }
+                    }
+                    TextAttr::Below(distance) => {
+                        pt.below = true;
+                        if let Some(distance) = distance {
+                            pt.shift -= super::eval::eval_len(ctx, distance)?.raw();
+                        }
+                    }
                     TextAttr::Center => pt.center = true,
                     TextAttr::LJust => pt.ljust = true,
                     TextAttr::RJust => pt.rjust = true,
@@ -145,7 +162,7 @@ impl PositionedText {
                 }
             }
         }
-        pt
+        Ok(pt)
     }
 
     /// Font scale factor: 1.25 for big, 0.8 for small, 1.0 otherwise
@@ -300,6 +317,21 @@ impl RenderedObject {
             None
         }
     }
+
+    /// Extent as drawn, including text and arrowheads (what the SVG viewBox covers)
+    pub fn bounds(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::new();
+        super::expand_object_bounds(&mut bounds, self);
+        bounds
+    }
+
+    /// Call `f` with this object, then depth-first with any `[]` sublist children
+    pub fn visit(&self, f: &mut impl FnMut(&RenderedObject)) {
+        f(self);
+        for child in self.children().unwrap_or_default() {
+            child.visit(f);
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -309,11 +341,6 @@ pub struct EndpointObject {
     pub width: Inches,
     pub height: Inches,
     pub corner_radius: Inches,
-    /// True if this object is inside a sublist (dotted name like Ptr.A)
-    /// C pikchr does NOT trigger implicit autochop for dotted names,
-    /// but explicit `chop` attribute still works.
-    /// cref: pik_position_from_place (pikchr.c) - doesn't set ppObj for dotted names
-    pub is_dotted_name: bool,
 }
 
 impl EndpointObject {
@@ -324,19 +351,6 @@ impl EndpointObject {
             width: obj.shape.width(),
             height: obj.shape.height(),
             corner_radius: obj.shape.style().corner_radius,
-            is_dotted_name: false,
-        }
-    }
-
-    /// Create from rendered object, marking it as a dotted name (object inside sublist)
-    pub fn from_rendered_dotted(obj: &RenderedObject) -> Self {
-        Self {
-            class: obj.shape.class(),
-            center: obj.shape.center(),
-            width: obj.shape.width(),
-            height: obj.shape.height(),
-            corner_radius: obj.shape.style().corner_radius,
-            is_dotted_name: true,
         }
     }
 }
@@ -345,6 +359,27 @@ impl EndpointObject {
 pub use crate::ast::ClassName;
 
 impl ClassName {
+    /// The source keyword for this class (`[]` for sublists)
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Self::Arc => "arc",
+            Self::Arrow => "arrow",
+            Self::Box => "box",
+            Self::Circle => "circle",
+            Self::Cylinder => "cylinder",
+            Self::Diamond => "diamond",
+            Self::Dot => "dot",
+            Self::Ellipse => "ellipse",
+            Self::File => "file",
+            Self::Line => "line",
+            Self::Move => "move",
+            Self::Oval => "oval",
+            Self::Spline => "spline",
+            Self::Sublist => "[]",
+            Self::Text => "text",
+        }
+    }
+
     /// Returns true if this is a round shape (circle, ellipse, oval)
     pub fn is_round(self) -> bool {
         matches!(self, Self::Circle | Self::Ellipse | Self::Oval)
@@ -5,6 +5,8 @@
 //! - Illegal states unrepresentable
 //! - Conversions only via Scaler
 
+#[cfg(feature = "facet")]
+use facet::Facet;
 use std::fmt;
 use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
 
@@ -14,6 +16,7 @@ use glam::DVec2;
 
 /// A location in source code (byte offsets)
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "facet", derive(Facet))]
 pub struct Span {
     /// Byte offset of start (inclusive)
     pub start: usize,
@@ -624,7 +627,12 @@ impl Color {
                     "yellow" => "rgb(255,255,0)".to_string(),
                     "yellowgreen" => "rgb(154,205,50)".to_string(),
                     "none" | "off" => "none".to_string(),
-                    _ => name.clone(),
+                    // Numeric colors (e.g. `color 0x123456`) arrive as "#rrggbb";
+                    // C always emits rgb() so structural comparison matches
+                    _ => match hex_to_rgb(name) {
+                        Some((r, g, b)) => format!("rgb({},{},{})", r, g, b),
+                        None => name.clone(),
+                    },
                 }
             }
         }
@@ -661,6 +669,64 @@ impl Color {
     }
 }
 
+/// Convert `rgb(r,g,b)` or `hsl(h,s%,l%)` (pikru extension) to `#rrggbb`, which the
+/// rest of the pipeline handles like a hex literal. `None` if an argument is out of range.
+pub fn css_color_to_hex(s: &str) -> Option<String> {
+    let (func, args) = s.strip_suffix(')')?.split_once('(')?;
+    let args: Vec<&str> = args.split(',').map(str::trim).collect();
+    let [a, b, c] = args[..] else {
+        return None;
+    };
+    let (r, g, b) = match func {
+        "rgb" => {
+            let channel = |v: &str| v.parse::<u8>().ok();
+            (channel(a)?, channel(b)?, channel(c)?)
+        }
+        "hsl" => {
+            let percent = |v: &str| {
+                let v = v.strip_suffix('%')?.parse::<f64>().ok()?;
+                (0.0..=100.0).contains(&v).then_some(v / 100.0)
+            };
+            hsl_to_rgb(a.parse::<f64>().ok()?, percent(b)?, percent(c)?)
+        }
+        _ => return None,
+    };
+    Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+}
+
+/// CSS Color 3 HSL to RGB; hue in degrees, saturation and lightness in 0..=1.
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (u8, u8, u8) {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = l - chroma / 2.0;
+    let channel = |v: f64| ((v + m) * 255.0).round() as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+/// Parse `#rgb`, `#rrggbb` or `#rrggbbaa` (alpha ignored) into RGB components.
+fn hex_to_rgb(s: &str) -> Option<(u8, u8, u8)> {
+    let hex = s.strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok();
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match hex.len() {
+        3 => Some((digit(0)? * 17, digit(1)? * 17, digit(2)? * 17)),
+        6 | 8 => Some((byte(0)?, byte(2)?, byte(4)?)),
+        _ => None,
+    }
+}
+
 impl std::str::FromStr for Color {
     type Err = std::convert::Infallible;
 
@@ -1413,6 +1479,62 @@ mod tests {
         assert_eq!(bb.max.y, Length(6.0));
     }
 
+    // ==================== Color tests ====================
+
+    #[test]
+    fn color_named_values_match_c() {
+        // Sampled from C pikchr's aColor[] table
+        let cases = [
+            ("Bisque", "rgb(255,228,196)"),
+            ("wheat", "rgb(245,222,179)"),
+            ("Gray", "rgb(128,128,128)"),
+            ("LightGrey", "rgb(211,211,211)"),
+            ("RebeccaPurple", "rgb(102,51,153)"),
+            ("none", "none"),
+        ];
+        for (name, rgb) in cases {
+            let color: Color = name.parse().unwrap();
+            assert_eq!(color.to_rgb_string(), rgb, "color {}", name);
+        }
+    }
+
+    #[test]
+    fn color_hex_to_rgb_string() {
+        let color: Color = "#123456".parse().unwrap();
+        assert_eq!(color.to_rgb_string(), "rgb(18,52,86)");
+        let color: Color = "#fa0".parse().unwrap();
+        assert_eq!(color.to_rgb_string(), "rgb(255,170,0)");
+        assert_eq!(color.to_u32(), 0xffaa00);
+    }
+
+    #[test]
+    fn css_color_functions_to_hex() {
+        let cases = [
+            ("rgb(255,0,0)", "#ff0000"),
+            ("rgb(18, 52, 86)", "#123456"),
+            ("hsl(0,100%,50%)", "#ff0000"),
+            ("hsl(120,100%,25%)", "#008000"),
+            ("hsl(240,100%,50%)", "#0000ff"),
+            ("hsl(210,50%,40%)", "#336699"),
+            ("hsl(-60,100%,50%)", "#ff00ff"),
+            ("hsl(0,0%,100%)", "#ffffff"),
+            ("hsl(39,100%,50%)", "#ffa600"),
+        ];
+        for (css, hex) in cases {
+            assert_eq!(css_color_to_hex(css).as_deref(), Some(hex), "color {}", css);
+        }
+        for bad in [
+            "rgb(256,0,0)",
+            "rgb(1,2)",
+            "hsl(0,101%,50%)",
+            "rgb(1.5,0,0)",
+            "rgb(-1,0,0)",
+            "hsl(0,100,50)",
+        ] {
+            assert_eq!(css_color_to_hex(bad), None, "color {}", bad);
+        }
+    }
+
     // ==================== Angle tests ====================
 
     #[test]
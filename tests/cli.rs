@@ -0,0 +1,79 @@
+//! Tests for the `pikru` binary, which stands in for the C `pikchr` shell
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pikru"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start pikru");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn svg_only_from_stdin() {
+    let output = run(&["--svg-only"], "box \"a<b\"");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(
+        stdout,
+        format!("{}\n", pikru::pikchr("box \"a<b\"").unwrap())
+    );
+
+    // A lone "-" also means stdin, and one dash works for options like in C
+    let output = run(&["-svg-only", "-"], "circle");
+    assert!(output.status.success());
+    assert!(
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .starts_with("<svg")
+    );
+}
+
+#[test]
+fn html_wrapper() {
+    let output = run(&[], "box \"a<b\"");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("<!DOCTYPE html>\n"), "{}", stdout);
+    assert!(stdout.ends_with("</body></html>\n"), "{}", stdout);
+    // Same page layout as C pikchr, which sizes the frame from the SVG width
+    for expected in [
+        "<h1>File -</h1>\n<div id=\"svg-1\" onclick=\"toggleHidden('svg-1')\">\n",
+        "<div style='border:3px solid lightgray;max-width:112px;'>\n<svg",
+        "</svg>\n</div>\n<pre class='hidden'>box \"a&lt;b\"</pre>\n</div>\n",
+    ] {
+        assert!(
+            stdout.contains(expected),
+            "missing {:?}: {}",
+            expected,
+            stdout
+        );
+    }
+}
+
+#[test]
+fn errors_go_to_stderr() {
+    let output = run(&["--svg-only"], "box wid");
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(!output.stderr.is_empty());
+
+    let output = run(&["--frobnicate"], "");
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("unknown option")
+    );
+}
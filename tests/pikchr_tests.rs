@@ -61,8 +61,16 @@ fn test_pikchr_file(path: &Utf8Path) -> datatest_stable::Result<()> {
     }
 
     match result {
-        CompareResult::SvgMismatch { ssim, details } => {
-            panic!("SVG mismatch for {} (SSIM: {:.6})\n{}", path, ssim, details);
+        CompareResult::SvgMismatch {
+            ssim,
+            details,
+            first_difference,
+        } => {
+            let first = first_difference.unwrap_or_else(|| "no attribute differs".to_string());
+            panic!(
+                "SVG mismatch for {} (SSIM: {:.6}): {}\n{}",
+                path, ssim, first, details
+            );
         }
         CompareResult::ParseError { details } => {
             panic!("Parse error for {}\n{}", path, details);
@@ -106,6 +114,8 @@ fn test_pikchr_file(path: &Utf8Path) -> datatest_stable::Result<()> {
     }
 }
 
+// Upstream pikchr tests, then pikru's own cases pinned against C
 datatest_stable::harness! {
     { test = test_pikchr_file, root = concat!(env!("CARGO_MANIFEST_DIR"), "/vendor/pikchr-c/tests"), pattern = r"\.pikchr$" },
+    { test = test_pikchr_file, root = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures"), pattern = r"\.pikchr$" },
 }